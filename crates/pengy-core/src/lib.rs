@@ -0,0 +1,7 @@
+//! Agent runtime: the model client, the tool-calling agent loop and the
+//! `ToolCall` trait that tools implement. Depends on neither the built-in
+//! tools nor the terminal UI, so servers and embedders can use it alone.
+
+pub mod agent;
+pub mod model;
+pub mod tool;
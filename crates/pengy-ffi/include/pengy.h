@@ -0,0 +1,92 @@
+/*
+ * C interface to PengyAgent (crates/pengy-ffi).
+ *
+ * Build with `cargo build --release -p pengy-ffi` and link against
+ * libpengy_ffi.so / libpengy_ffi.dylib / pengy_ffi.dll (or the static
+ * libpengy_ffi.a). All strings are NUL-terminated UTF-8. A panic inside
+ * the library never unwinds into the caller; it is reported like any other
+ * error (through *error_out where the function has one).
+ */
+#ifndef PENGY_H
+#define PENGY_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct PengyAgent PengyAgent;
+
+/*
+ * Receives each agent event as a JSON object with a "type" key: step,
+ * tool_call, tool_result, token_usage, thinking, final_response, error or
+ * vision_analysis. The string is only valid during the call.
+ */
+typedef void (*PengyEventCallback)(const char *event_json, void *user_data);
+
+/* Returns non-zero to allow `tool` to perform `action`. */
+typedef int (*PengyApprovalCallback)(const char *tool, const char *action, void *user_data);
+
+/*
+ * Chooses which of `count` proposed changes `tool` may apply. items_json is a
+ * JSON array of {"group", "label", "detail"} objects; set accepted[i] to
+ * non-zero for each item to keep (all start at zero). Return zero to cancel
+ * the whole operation.
+ */
+typedef int (*PengySelectionCallback)(const char *tool,
+                                      const char *title,
+                                      const char *items_json,
+                                      int *accepted,
+                                      size_t count,
+                                      void *user_data);
+
+/*
+ * Create an agent: agent_type is one of coder, code-researcher, test-agent,
+ * pengy-agent, control-agent, issue-agent or chat-agent. base_url may be NULL
+ * for OpenRouter. Returns NULL on failure and stores a message in *error_out
+ * (free it with pengy_string_free) when error_out is not NULL.
+ */
+PengyAgent *pengy_agent_new(const char *agent_type,
+                            const char *model,
+                            const char *api_key,
+                            const char *base_url,
+                            char **error_out);
+
+/*
+ * Send a prompt and block until the run finishes, calling `callback` (may be
+ * NULL) for every event on this thread. Later prompts continue the same
+ * conversation. Returns the final response (free with pengy_string_free), or
+ * NULL if there was none or the arguments were invalid (*error_out is set).
+ * A handle must not be used from two threads at once.
+ */
+char *pengy_agent_send(PengyAgent *agent,
+                       const char *prompt,
+                       PengyEventCallback callback,
+                       void *user_data,
+                       char **error_out);
+
+/* Destroy an agent. NULL is ignored. */
+void pengy_agent_free(PengyAgent *agent);
+
+/* Free a string returned by this library. NULL is ignored. */
+void pengy_string_free(char *value);
+
+/*
+ * Route tool approval requests to `callback` for the whole process. NULL
+ * restores the default: deny unless PENGY_AUTO_APPROVE=1 is set.
+ */
+void pengy_set_approval_callback(PengyApprovalCallback callback, void *user_data);
+
+/*
+ * Route selection requests (such as reviewing the hunks of a project-wide
+ * find_replace) to `callback` for the whole process. NULL restores the
+ * default: cancel unless PENGY_AUTO_APPROVE=1 is set.
+ */
+void pengy_set_selection_callback(PengySelectionCallback callback, void *user_data);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PENGY_H */
@@ -0,0 +1,342 @@
+pub mod c_api {
+    //! C ABI over [`AgentSession`]; declared in `include/pengy.h`.
+    //!
+    //! Strings cross the boundary as NUL-terminated UTF-8. Strings returned
+    //! by the library (errors, final responses) are owned by the caller and
+    //! released with `pengy_string_free`; event JSON passed to callbacks is
+    //! only valid for the duration of the call.
+    //!
+    //! Panics never unwind into the caller: entry points report them through
+    //! `error_out` (where they have one) and return NULL.
+
+    use crate::session::session::AgentSession;
+    use pengy_agent::tool::approval::approval;
+    use std::ffi::{CStr, CString, c_char, c_int, c_void};
+    use std::panic::{self, AssertUnwindSafe};
+    use std::ptr;
+
+    /// Receives each agent event as a JSON object.
+    pub type PengyEventCallback = extern "C" fn(event_json: *const c_char, user_data: *mut c_void);
+
+    /// Decides whether a tool may perform a sensitive action; non-zero
+    /// approves.
+    pub type PengyApprovalCallback =
+        extern "C" fn(tool: *const c_char, action: *const c_char, user_data: *mut c_void) -> c_int;
+
+    /// Chooses which of `count` proposed changes a tool may apply. The items
+    /// arrive as a JSON array of `{"group", "label", "detail"}` objects; the
+    /// callback sets `accepted[i]` to non-zero for each item to keep and
+    /// returns zero to cancel the whole operation.
+    pub type PengySelectionCallback = extern "C" fn(
+        tool: *const c_char,
+        title: *const c_char,
+        items_json: *const c_char,
+        accepted: *mut c_int,
+        count: usize,
+        user_data: *mut c_void,
+    ) -> c_int;
+
+    /// Opaque handle returned by `pengy_agent_new`.
+    pub struct PengyAgent {
+        session: AgentSession,
+    }
+
+    /// Caller-provided context pointer handed back to callbacks. The caller
+    /// promises it may be used from the thread running the agent.
+    #[derive(Clone, Copy)]
+    struct UserData(*mut c_void);
+    unsafe impl Send for UserData {}
+    unsafe impl Sync for UserData {}
+
+    unsafe fn read_str<'a>(value: *const c_char, name: &str) -> Result<&'a str, String> {
+        if value.is_null() {
+            return Err(format!("{} must not be NULL", name));
+        }
+        unsafe { CStr::from_ptr(value) }
+            .to_str()
+            .map_err(|_| format!("{} is not valid UTF-8", name))
+    }
+
+    fn into_c_string(value: String) -> *mut c_char {
+        CString::new(value.replace('\0', ""))
+            .map(CString::into_raw)
+            .unwrap_or(ptr::null_mut())
+    }
+
+    unsafe fn set_error(error_out: *mut *mut c_char, message: String) {
+        if !error_out.is_null() {
+            unsafe { *error_out = into_c_string(message) };
+        }
+    }
+
+    /// Run `body`, turning a panic into an error instead of unwinding across
+    /// the C boundary.
+    fn catch_panic<T>(body: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+        panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+            let detail = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_string());
+            Err(format!("internal panic: {}", detail))
+        })
+    }
+
+    /// Create an agent. `base_url` may be NULL for OpenRouter. Returns NULL
+    /// on failure and, if `error_out` is not NULL, stores an error message
+    /// there.
+    ///
+    /// # Safety
+    /// String arguments must be NULL or valid NUL-terminated strings;
+    /// `error_out` must be NULL or point to writable memory.
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn pengy_agent_new(
+        agent_type: *const c_char,
+        model: *const c_char,
+        api_key: *const c_char,
+        base_url: *const c_char,
+        error_out: *mut *mut c_char,
+    ) -> *mut PengyAgent {
+        let created = catch_panic(|| {
+            let agent_type = unsafe { read_str(agent_type, "agent_type") }?;
+            let model = unsafe { read_str(model, "model") }?;
+            let api_key = unsafe { read_str(api_key, "api_key") }?;
+            let base_url = if base_url.is_null() {
+                None
+            } else {
+                Some(unsafe { read_str(base_url, "base_url") }?)
+            };
+            AgentSession::new(agent_type, model, api_key, base_url)
+        });
+        match created {
+            Ok(session) => Box::into_raw(Box::new(PengyAgent { session })),
+            Err(err) => {
+                unsafe { set_error(error_out, err) };
+                ptr::null_mut()
+            }
+        }
+    }
+
+    /// Send `prompt` and block until the run finishes, invoking `callback`
+    /// (which may be NULL) for every event on the calling thread. Returns
+    /// the final response, or NULL if the run produced none, the arguments
+    /// were invalid or the run panicked (in the last two cases `error_out`
+    /// is set).
+    ///
+    /// # Safety
+    /// `agent` must come from `pengy_agent_new` and not be used concurrently;
+    /// `prompt` must be a valid NUL-terminated string; `error_out` must be
+    /// NULL or point to writable memory.
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn pengy_agent_send(
+        agent: *mut PengyAgent,
+        prompt: *const c_char,
+        callback: Option<PengyEventCallback>,
+        user_data: *mut c_void,
+        error_out: *mut *mut c_char,
+    ) -> *mut c_char {
+        let response = catch_panic(|| {
+            let agent = unsafe { agent.as_mut() }.ok_or("agent must not be NULL")?;
+            let prompt = unsafe { read_str(prompt, "prompt") }?;
+
+            let user_data = UserData(user_data);
+            let on_event = move |event: &str| {
+                let user_data = user_data;
+                if let Some(callback) = callback
+                    && let Ok(event) = CString::new(event)
+                {
+                    callback(event.as_ptr(), user_data.0);
+                }
+            };
+            Ok(agent.session.send(prompt, on_event))
+        });
+        match response {
+            Ok(response) => response.map(into_c_string).unwrap_or(ptr::null_mut()),
+            Err(err) => {
+                unsafe { set_error(error_out, err) };
+                ptr::null_mut()
+            }
+        }
+    }
+
+    /// Destroy an agent created by `pengy_agent_new`. NULL is ignored.
+    ///
+    /// # Safety
+    /// `agent` must be NULL or a handle that has not been freed yet.
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn pengy_agent_free(agent: *mut PengyAgent) {
+        if !agent.is_null() {
+            drop(unsafe { Box::from_raw(agent) });
+        }
+    }
+
+    /// Release a string returned by this library. NULL is ignored.
+    ///
+    /// # Safety
+    /// `value` must be NULL or a string returned by this library that has not
+    /// been freed yet.
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn pengy_string_free(value: *mut c_char) {
+        if !value.is_null() {
+            drop(unsafe { CString::from_raw(value) });
+        }
+    }
+
+    /// Route tool approval requests (for example making a file executable)
+    /// to `callback`, process-wide. NULL restores the default, which denies
+    /// every request unless `PENGY_AUTO_APPROVE=1` is set.
+    ///
+    /// # Safety
+    /// `user_data` must stay valid until the callback is replaced.
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn pengy_set_approval_callback(
+        callback: Option<PengyApprovalCallback>,
+        user_data: *mut c_void,
+    ) {
+        let _ = catch_panic(|| {
+            let Some(callback) = callback else {
+                approval::clear_handler();
+                return Ok(());
+            };
+            let user_data = UserData(user_data);
+            approval::set_handler(Box::new(move |request| {
+                let user_data = user_data;
+                let (Ok(tool), Ok(action)) = (
+                    CString::new(request.tool.as_str()),
+                    CString::new(request.action.as_str()),
+                ) else {
+                    return false;
+                };
+                callback(tool.as_ptr(), action.as_ptr(), user_data.0) != 0
+            }));
+            Ok(())
+        });
+    }
+
+    /// Route selection requests (for example reviewing the hunks of a
+    /// project-wide find_replace) to `callback`, process-wide. NULL restores
+    /// the default, which cancels every selection unless
+    /// `PENGY_AUTO_APPROVE=1` is set.
+    ///
+    /// # Safety
+    /// `user_data` must stay valid until the callback is replaced.
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn pengy_set_selection_callback(
+        callback: Option<PengySelectionCallback>,
+        user_data: *mut c_void,
+    ) {
+        let _ = catch_panic(|| {
+            let Some(callback) = callback else {
+                approval::clear_selection_handler();
+                return Ok(());
+            };
+            let user_data = UserData(user_data);
+            approval::set_selection_handler(Box::new(move |request| {
+                let user_data = user_data;
+                let items: Vec<serde_json::Value> = request
+                    .items
+                    .iter()
+                    .map(|item| {
+                        serde_json::json!({
+                            "group": item.group,
+                            "label": item.label,
+                            "detail": item.detail,
+                        })
+                    })
+                    .collect();
+                let (Ok(tool), Ok(title), Ok(items_json)) = (
+                    CString::new(request.tool.as_str()),
+                    CString::new(request.title.as_str()),
+                    CString::new(serde_json::Value::Array(items).to_string()),
+                ) else {
+                    return None;
+                };
+                let mut accepted: Vec<c_int> = vec![0; request.items.len()];
+                let proceed = callback(
+                    tool.as_ptr(),
+                    title.as_ptr(),
+                    items_json.as_ptr(),
+                    accepted.as_mut_ptr(),
+                    accepted.len(),
+                    user_data.0,
+                );
+                (proceed != 0).then(|| accepted.iter().map(|flag| *flag != 0).collect())
+            }));
+            Ok(())
+        });
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn invalid_arguments_report_errors() {
+            let mut error: *mut c_char = ptr::null_mut();
+            let agent = unsafe {
+                pengy_agent_new(
+                    c"spaceship".as_ptr(),
+                    c"openai/gpt-4o".as_ptr(),
+                    c"key".as_ptr(),
+                    ptr::null(),
+                    &mut error,
+                )
+            };
+            assert!(agent.is_null());
+            let message = unsafe { CStr::from_ptr(error) }.to_str().unwrap();
+            assert!(message.starts_with("Unknown agent type: spaceship"));
+            unsafe { pengy_string_free(error) };
+
+            let mut error: *mut c_char = ptr::null_mut();
+            let response = unsafe {
+                pengy_agent_send(
+                    ptr::null_mut(),
+                    c"hi".as_ptr(),
+                    None,
+                    ptr::null_mut(),
+                    &mut error,
+                )
+            };
+            assert!(response.is_null());
+            assert_eq!(
+                unsafe { CStr::from_ptr(error) }.to_str().unwrap(),
+                "agent must not be NULL"
+            );
+            unsafe { pengy_string_free(error) };
+        }
+
+        #[test]
+        fn panics_become_errors() {
+            let result: Result<(), String> = catch_panic(|| panic!("boom"));
+            assert_eq!(result.unwrap_err(), "internal panic: boom");
+        }
+
+        extern "C" fn accept_second(
+            _tool: *const c_char,
+            _title: *const c_char,
+            items_json: *const c_char,
+            accepted: *mut c_int,
+            count: usize,
+            _user_data: *mut c_void,
+        ) -> c_int {
+            let items = unsafe { CStr::from_ptr(items_json) }.to_str().unwrap();
+            assert!(items.contains("\"label\":\"b\""));
+            let accepted = unsafe { std::slice::from_raw_parts_mut(accepted, count) };
+            accepted[1] = 1;
+            1
+        }
+
+        #[test]
+        fn selection_callback_chooses_items() {
+            let item = |label: &str| approval::SelectionItem {
+                group: "src/lib.rs".to_string(),
+                label: label.to_string(),
+                detail: String::new(),
+            };
+            unsafe { pengy_set_selection_callback(Some(accept_second), ptr::null_mut()) };
+            let accepted = approval::select("find_replace", "replace", vec![item("a"), item("b")]);
+            unsafe { pengy_set_selection_callback(None, ptr::null_mut()) };
+            assert_eq!(accepted, Some(vec![false, true]));
+        }
+    }
+}
@@ -0,0 +1,8 @@
+//! Embedding API for the ready-made agents: a C ABI (`include/pengy.h`) and,
+//! with the `python` feature, a Python module built with maturin. Both run
+//! agents in-process and deliver agent events to callbacks as JSON.
+
+pub mod c_api;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod session;
@@ -0,0 +1,131 @@
+pub mod python {
+    //! Python bindings (the `python` feature), built with maturin:
+    //!
+    //! ```python
+    //! import pengy
+    //! agent = pengy.Agent("coder", "openai/gpt-4o", api_key)
+    //! answer = agent.send("Add a --verbose flag", on_event=print)
+    //! ```
+    //!
+    //! `on_event` receives each event as a dict with a `"type"` key. The GIL
+    //! is released while the agent runs and re-acquired for callbacks.
+
+    use crate::session::session::AgentSession;
+    use pengy_agent::tool::approval::approval;
+    use pyo3::exceptions::PyValueError;
+    use pyo3::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    /// A conversation with one agent; see `AgentSession`.
+    #[pyclass(name = "Agent")]
+    struct PyAgent {
+        session: AgentSession,
+    }
+
+    #[pymethods]
+    impl PyAgent {
+        #[new]
+        #[pyo3(signature = (agent_type, model, api_key, base_url = None))]
+        fn new(
+            agent_type: &str,
+            model: &str,
+            api_key: &str,
+            base_url: Option<&str>,
+        ) -> PyResult<Self> {
+            AgentSession::new(agent_type, model, api_key, base_url)
+                .map(|session| Self { session })
+                .map_err(PyValueError::new_err)
+        }
+
+        /// Run `prompt` to completion and return the final response (or
+        /// `None`). An exception raised by `on_event` is re-raised once the
+        /// run has finished.
+        #[pyo3(signature = (prompt, on_event = None))]
+        fn send(
+            &mut self,
+            py: Python<'_>,
+            prompt: &str,
+            on_event: Option<Py<PyAny>>,
+        ) -> PyResult<Option<String>> {
+            let failure: Arc<Mutex<Option<PyErr>>> = Default::default();
+            let captured = Arc::clone(&failure);
+            let on_event = move |event: &str| {
+                let Some(callback) = on_event.as_ref() else {
+                    return;
+                };
+                Python::attach(|py| {
+                    let delivered = py
+                        .import("json")
+                        .and_then(|json| json.call_method1("loads", (event,)))
+                        .and_then(|event| callback.call1(py, (event,)));
+                    if let Err(err) = delivered
+                        && let Ok(mut slot) = captured.lock()
+                        && slot.is_none()
+                    {
+                        *slot = Some(err);
+                    }
+                });
+            };
+            let session = &mut self.session;
+            let response = py.detach(|| session.send(prompt, on_event));
+            match failure.lock().ok().and_then(|mut slot| slot.take()) {
+                Some(err) => Err(err),
+                None => Ok(response),
+            }
+        }
+    }
+
+    /// Route tool approval requests to `handler(tool, action) -> bool`, or
+    /// restore the default (deny unless `PENGY_AUTO_APPROVE=1`) with `None`.
+    #[pyfunction]
+    #[pyo3(signature = (handler = None))]
+    fn set_approval_handler(handler: Option<Py<PyAny>>) {
+        let Some(handler) = handler else {
+            approval::clear_handler();
+            return;
+        };
+        approval::set_handler(Box::new(move |request| {
+            Python::attach(|py| {
+                handler
+                    .call1(py, (request.tool.as_str(), request.action.as_str()))
+                    .and_then(|answer| answer.is_truthy(py))
+                    .unwrap_or(false)
+            })
+        }));
+    }
+
+    /// Route selection requests to `handler(tool, title, items)`, where
+    /// `items` is a list of `(group, label, detail)` tuples. The handler
+    /// returns one bool per item, or `None` to cancel; `None` as the handler
+    /// restores the default (cancel unless `PENGY_AUTO_APPROVE=1`).
+    #[pyfunction]
+    #[pyo3(signature = (handler = None))]
+    fn set_selection_handler(handler: Option<Py<PyAny>>) {
+        let Some(handler) = handler else {
+            approval::clear_selection_handler();
+            return;
+        };
+        approval::set_selection_handler(Box::new(move |request| {
+            let items: Vec<(String, String, String)> = request
+                .items
+                .iter()
+                .map(|item| (item.group.clone(), item.label.clone(), item.detail.clone()))
+                .collect();
+            Python::attach(|py| {
+                handler
+                    .call1(py, (request.tool.as_str(), request.title.as_str(), items))
+                    .and_then(|answer| answer.extract::<Option<Vec<bool>>>(py))
+                    .unwrap_or(None)
+            })
+        }));
+    }
+
+    #[pymodule]
+    #[pyo3(name = "pengy")]
+    fn pengy_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        m.add_class::<PyAgent>()?;
+        m.add_function(wrap_pyfunction!(set_approval_handler, m)?)?;
+        m.add_function(wrap_pyfunction!(set_selection_handler, m)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+pub mod session {
+    //! Language-neutral wrapper around the ready-made agents shared by the C
+    //! ABI and the Python module: one agent per session, a private tokio
+    //! runtime per session, and agent events serialized to JSON.
+
+    use pengy_agent::agent::agent::agent::{Agent, AgentEvent};
+    use pengy_agent::agent::chat_agent::chat_agent::create_chat_agent;
+    use pengy_agent::agent::code_researcher::code_researcher::create_code_researcher_agent;
+    use pengy_agent::agent::coder_v2::coder_v2::create_coder_v2_agent;
+    use pengy_agent::agent::control_agent::control_agent::create_control_agent;
+    use pengy_agent::agent::issue_agent::issue_agent::create_issue_agent;
+    use pengy_agent::agent::pengy_agent::pengy_agent::run_pengy_agent;
+    use pengy_agent::agent::test_agent::test_agent::create_test_agent;
+    use pengy_agent::model::model::model::Model;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use tokio::runtime::Runtime;
+
+    pub const DEFAULT_BASE_URL: &str = "https://openrouter.ai/api/v1";
+    const EMBEDDING_MODEL: &str = "openai/text-embedding-3-small";
+    const MAX_RETRY: u32 = 3;
+    const MAX_STEPS: u32 = 50;
+
+    enum Runner {
+        Agent(Box<Agent>),
+        /// The pengy agent plans and delegates per prompt, so it keeps no
+        /// conversation between sends.
+        Pengy {
+            model: Model,
+            api_key: String,
+            base_url: String,
+        },
+    }
+
+    /// An agent and the runtime that drives it. Each `send` continues the
+    /// same conversation (except for the stateless `pengy` agent).
+    pub struct AgentSession {
+        runtime: Runtime,
+        runner: Runner,
+    }
+
+    impl AgentSession {
+        /// Create a session for `agent_type` (coder, code-researcher,
+        /// test-agent, pengy-agent, control-agent, issue-agent or
+        /// chat-agent, with the same aliases as `pengy-cmd`).
+        pub fn new(
+            agent_type: &str,
+            model_name: &str,
+            api_key: &str,
+            base_url: Option<&str>,
+        ) -> Result<Self, String> {
+            let base_url = base_url
+                .map(|url| url.trim().trim_end_matches('/'))
+                .filter(|url| !url.is_empty())
+                .unwrap_or(DEFAULT_BASE_URL)
+                .to_string();
+            let model = Model::new(
+                model_name.to_string(),
+                api_key.to_string(),
+                base_url.clone(),
+            );
+            let (retry, steps) = (Some(MAX_RETRY), Some(MAX_STEPS));
+            let runner = match agent_type.to_lowercase().as_str() {
+                "coder" | "coder-agent" => {
+                    Runner::Agent(Box::new(create_coder_v2_agent(model, None, retry, steps)))
+                }
+                "code-researcher" | "researcher" | "code_researcher" => {
+                    Runner::Agent(Box::new(create_code_researcher_agent(
+                        model,
+                        api_key.to_string(),
+                        base_url,
+                        Some(EMBEDDING_MODEL.to_string()),
+                        None,
+                        retry,
+                        steps,
+                    )))
+                }
+                "test-agent" | "test" | "test_agent" => {
+                    Runner::Agent(Box::new(create_test_agent(model, None, retry, steps)))
+                }
+                "control-agent" | "control" | "control_agent" => {
+                    Runner::Agent(Box::new(create_control_agent(model, None, retry, steps)))
+                }
+                "issue-agent" | "issue" | "issue_agent" => {
+                    Runner::Agent(Box::new(create_issue_agent(model, None, retry, steps)))
+                }
+                "chat-agent" | "chat" | "chat_agent" => {
+                    Runner::Agent(Box::new(create_chat_agent(model, None, retry, steps)))
+                }
+                "pengy-agent" | "pengy" | "pengy_agent" => Runner::Pengy {
+                    model,
+                    api_key: api_key.to_string(),
+                    base_url,
+                },
+                other => {
+                    return Err(format!(
+                        "Unknown agent type: {}. Available: coder, code-researcher, test-agent, pengy-agent, control-agent, issue-agent, chat-agent",
+                        other
+                    ));
+                }
+            };
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| format!("Failed to create async runtime: {}", e))?;
+            Ok(Self { runtime, runner })
+        }
+
+        /// Run `prompt` to completion, passing every event to `on_event` as a
+        /// JSON object (see [`event_json`]). Returns the final response, if
+        /// the agent produced one.
+        pub fn send<F>(&mut self, prompt: &str, on_event: F) -> Option<String>
+        where
+            F: Fn(&str) + Send + Sync + 'static,
+        {
+            let final_response = Arc::new(Mutex::new(None));
+            let captured = Arc::clone(&final_response);
+            let on_event = Arc::new(on_event);
+            let callback = move |event: AgentEvent| {
+                if let AgentEvent::FinalResponse { content } = &event
+                    && let Ok(mut slot) = captured.lock()
+                {
+                    *slot = Some(content.clone());
+                }
+                on_event(&event_json(&event));
+            };
+
+            let prompt = prompt.to_string();
+            match &mut self.runner {
+                Runner::Agent(agent) => self.runtime.block_on(agent.run(prompt, callback)),
+                Runner::Pengy {
+                    model,
+                    api_key,
+                    base_url,
+                } => {
+                    let _ = self.runtime.block_on(run_pengy_agent(
+                        model.clone(),
+                        api_key.clone(),
+                        base_url.clone(),
+                        Some(EMBEDDING_MODEL.to_string()),
+                        prompt,
+                        None,
+                        Some(MAX_RETRY),
+                        Some(MAX_STEPS),
+                        callback,
+                    ));
+                }
+            }
+            final_response.lock().ok().and_then(|mut slot| slot.take())
+        }
+    }
+
+    /// Serialize an agent event as `{"type": "<snake_case kind>", ...fields}`.
+    pub fn event_json(event: &AgentEvent) -> String {
+        let value = match event {
+            AgentEvent::Step { step, max_steps } => {
+                json!({ "type": "step", "step": step, "max_steps": max_steps })
+            }
+            AgentEvent::ToolCall { tool_name, args } => {
+                json!({ "type": "tool_call", "tool_name": tool_name, "args": args })
+            }
+            AgentEvent::ToolResult { result } => json!({ "type": "tool_result", "result": result }),
+            AgentEvent::TokenUsage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens,
+            } => json!({
+                "type": "token_usage",
+                "prompt_tokens": prompt_tokens,
+                "completion_tokens": completion_tokens,
+                "total_tokens": total_tokens,
+            }),
+            AgentEvent::Thinking { content } => json!({ "type": "thinking", "content": content }),
+            AgentEvent::FinalResponse { content } => {
+                json!({ "type": "final_response", "content": content })
+            }
+            AgentEvent::Error { error } => json!({ "type": "error", "error": error }),
+            AgentEvent::VisionAnalysis { status } => {
+                json!({ "type": "vision_analysis", "status": status })
+            }
+        };
+        value.to_string()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn events_serialize_with_type_tag() {
+            let event = AgentEvent::ToolCall {
+                tool_name: "bash".to_string(),
+                args: "{\"command\":\"ls\"}".to_string(),
+            };
+            let value: serde_json::Value = serde_json::from_str(&event_json(&event)).unwrap();
+            assert_eq!(value["type"], "tool_call");
+            assert_eq!(value["tool_name"], "bash");
+            assert_eq!(value["args"], "{\"command\":\"ls\"}");
+
+            let usage = AgentEvent::TokenUsage {
+                prompt_tokens: Some(10),
+                completion_tokens: None,
+                total_tokens: Some(10),
+            };
+            let value: serde_json::Value = serde_json::from_str(&event_json(&usage)).unwrap();
+            assert_eq!(value["type"], "token_usage");
+            assert!(value["completion_tokens"].is_null());
+
+            assert!(AgentSession::new("nope", "m", "k", None).is_err());
+        }
+    }
+}
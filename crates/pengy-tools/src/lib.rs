@@ -0,0 +1,6 @@
+//! Built-in tools (shell, file editing, search, web, documents, vision) and
+//! the workspace helpers they share, implemented against the `ToolCall`
+//! trait from `pengy-core`.
+
+pub mod tool;
+pub mod util;
@@ -0,0 +1,165 @@
+pub mod approval {
+    //! Process-wide hook that lets tools ask the user before performing
+    //! sensitive actions (for example making a file executable).
+    //!
+    //! Front-ends install a handler with [`set_handler`]; tools call
+    //! [`request`] and proceed only when it returns `true`. Without a handler
+    //! every request is denied unless `PENGY_AUTO_APPROVE=1` is set, so
+    //! headless runs never escalate silently.
+    //!
+    //! Tools that need the user to pick a subset of proposed changes (such as
+    //! project-wide find_replace hunks) use [`select`] with a handler installed
+    //! by [`set_selection_handler`]. Without one the selection is cancelled
+    //! under the same policy: every item is accepted only with
+    //! `PENGY_AUTO_APPROVE=1`.
+
+    use std::sync::RwLock;
+
+    /// A single action awaiting the user's decision.
+    #[derive(Debug, Clone)]
+    pub struct ApprovalRequest {
+        /// Name of the tool asking for approval.
+        pub tool: String,
+        /// Human-readable description of what will happen if approved.
+        pub action: String,
+    }
+
+    /// Callback that decides whether an action may proceed. It may block
+    /// until the user answers.
+    pub type ApprovalHandler = Box<dyn Fn(&ApprovalRequest) -> bool + Send + Sync>;
+
+    /// One choosable item, such as a single replacement hunk.
+    #[derive(Debug, Clone)]
+    pub struct SelectionItem {
+        /// Heading the item is listed under (e.g. the file path).
+        pub group: String,
+        /// Short one-line description.
+        pub label: String,
+        /// Longer preview shown for the highlighted item.
+        pub detail: String,
+    }
+
+    /// A set of items the user can include or exclude before a tool proceeds.
+    #[derive(Debug, Clone)]
+    pub struct SelectionRequest {
+        pub tool: String,
+        pub title: String,
+        pub items: Vec<SelectionItem>,
+    }
+
+    /// Callback returning which items were accepted (one flag per item), or
+    /// `None` when the user cancelled the whole operation.
+    pub type SelectionHandler = Box<dyn Fn(&SelectionRequest) -> Option<Vec<bool>> + Send + Sync>;
+
+    static HANDLER: RwLock<Option<ApprovalHandler>> = RwLock::new(None);
+    static SELECTION_HANDLER: RwLock<Option<SelectionHandler>> = RwLock::new(None);
+
+    /// Install the handler used for all subsequent approval requests.
+    pub fn set_handler(handler: ApprovalHandler) {
+        if let Ok(mut slot) = HANDLER.write() {
+            *slot = Some(handler);
+        }
+    }
+
+    /// Remove the installed handler, reverting to the default policy.
+    pub fn clear_handler() {
+        if let Ok(mut slot) = HANDLER.write() {
+            *slot = None;
+        }
+    }
+
+    /// Ask whether `tool` may perform `action`.
+    pub fn request(tool: &str, action: &str) -> bool {
+        let request = ApprovalRequest {
+            tool: tool.to_string(),
+            action: action.to_string(),
+        };
+        if let Ok(slot) = HANDLER.read()
+            && let Some(handler) = slot.as_ref()
+        {
+            return handler(&request);
+        }
+        auto_approve()
+    }
+
+    /// Install the handler used for selection requests.
+    pub fn set_selection_handler(handler: SelectionHandler) {
+        if let Ok(mut slot) = SELECTION_HANDLER.write() {
+            *slot = Some(handler);
+        }
+    }
+
+    /// Remove the installed selection handler, reverting to the default
+    /// policy.
+    pub fn clear_selection_handler() {
+        if let Ok(mut slot) = SELECTION_HANDLER.write() {
+            *slot = None;
+        }
+    }
+
+    /// Let the user choose which of `items` `tool` should apply. Returns one
+    /// flag per item, or `None` if the user cancelled (or nobody can be
+    /// asked and auto-approval is off).
+    pub fn select(tool: &str, title: &str, items: Vec<SelectionItem>) -> Option<Vec<bool>> {
+        let request = SelectionRequest {
+            tool: tool.to_string(),
+            title: title.to_string(),
+            items,
+        };
+        if let Ok(slot) = SELECTION_HANDLER.read()
+            && let Some(handler) = slot.as_ref()
+        {
+            return handler(&request).map(|mut accepted| {
+                accepted.resize(request.items.len(), false);
+                accepted
+            });
+        }
+        auto_approve().then(|| vec![true; request.items.len()])
+    }
+
+    /// Selection handler for terminal front-ends: lists the items on stderr
+    /// and applies all of them or none based on a y/N answer.
+    pub fn stdin_selection_handler() -> SelectionHandler {
+        Box::new(|request: &SelectionRequest| {
+            let mut group = "";
+            eprintln!("[Review] {}: {}", request.tool, request.title);
+            for item in &request.items {
+                if item.group != group {
+                    group = &item.group;
+                    eprintln!("  {}", group);
+                }
+                eprintln!("    {}", item.label);
+            }
+            let approve = stdin_handler();
+            let accepted = approve(&ApprovalRequest {
+                tool: request.tool.clone(),
+                action: format!("apply all {} change(s)", request.items.len()),
+            });
+            accepted.then(|| vec![true; request.items.len()])
+        })
+    }
+
+    /// Handler for terminal front-ends: prints the request to stderr and reads
+    /// a y/N answer from stdin.
+    pub fn stdin_handler() -> ApprovalHandler {
+        Box::new(|request: &ApprovalRequest| {
+            use std::io::{BufRead, Write};
+            eprint!(
+                "[Approval] {} wants to {}. Allow? [y/N] ",
+                request.tool, request.action
+            );
+            let _ = std::io::stderr().flush();
+            let mut answer = String::new();
+            if std::io::stdin().lock().read_line(&mut answer).is_err() {
+                return false;
+            }
+            matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+        })
+    }
+
+    fn auto_approve() -> bool {
+        std::env::var("PENGY_AUTO_APPROVE")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,669 @@
+pub mod archive {
+    //! List and extract entries of zip, tar and tar.gz archives, e.g. release
+    //! artifacts, dependency sources or bundles supplied by the user.
+    //!
+    //! Extraction goes to a fresh directory under the system temp dir and is
+    //! capped in total size, which also guards against decompression bombs.
+    //! Entries with absolute or `..` paths, symlinks and special files are
+    //! skipped rather than written.
+    use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use crate::util::workspace_files::workspace_files;
+    use flate2::read::GzDecoder;
+    use serde_json;
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::fs::{self, File};
+    use std::io::{self, Read};
+    use std::path::{Component, Path, PathBuf};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Total bytes written by a single extraction.
+    const MAX_EXTRACT_BYTES: u64 = 64 * 1024 * 1024;
+    /// Entries shown by `list` and in extraction summaries.
+    const MAX_LISTED_ENTRIES: usize = 500;
+    /// GNU long-name and PAX header entries, which are buffered whole.
+    const MAX_METADATA_BYTES: u64 = 64 * 1024;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Format {
+        Zip,
+        Tar,
+        TarGz,
+    }
+
+    impl Format {
+        fn label(&self) -> &'static str {
+            match self {
+                Format::Zip => "zip",
+                Format::Tar => "tar",
+                Format::TarGz => "tar.gz",
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum EntryKind {
+        File,
+        Dir,
+        Symlink(String),
+        /// Hard links, devices, FIFOs and other entries that are never extracted.
+        Other,
+    }
+
+    #[derive(Debug, Clone)]
+    struct Entry {
+        name: String,
+        size: u64,
+        kind: EntryKind,
+    }
+
+    /// Called for every entry with a reader over its contents. Returning
+    /// `false` stops the walk.
+    type Visitor<'a> = dyn FnMut(&Entry, &mut dyn Read) -> Result<bool, Box<dyn Error>> + 'a;
+
+    /// Lists and extracts archive entries into a temporary directory.
+    pub struct ArchiveTool {
+        tool: Tool,
+        pub(crate) extract_root: PathBuf,
+    }
+
+    impl ArchiveTool {
+        pub fn new() -> Self {
+            let mut parameters = HashMap::new();
+
+            let mut path_items = HashMap::new();
+            path_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "archive_path".to_string(),
+                Parameter {
+                    items: path_items,
+                    description: "Path to a .zip, .tar, .tar.gz or .tgz file.".to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let mut action_items = HashMap::new();
+            action_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "action".to_string(),
+                Parameter {
+                    items: action_items,
+                    description: "'list' (default) shows the entries; 'extract' writes them to a new temp directory and returns its path.".to_string(),
+                    enum_values: Some(vec!["list".to_string(), "extract".to_string()]),
+                },
+            );
+
+            let mut entries_items = HashMap::new();
+            entries_items.insert("type".to_string(), "array".to_string());
+            entries_items.insert("item_type".to_string(), "string".to_string());
+            parameters.insert(
+                "entries".to_string(),
+                Parameter {
+                    items: entries_items,
+                    description: "Entries to extract: exact names, directory prefixes or globs ('*.rs', 'src/*'). Omit to extract everything (up to the size cap).".to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let tool = Tool {
+                name: "archive".to_string(),
+                description: "Inspect zip/tar/tar.gz archives: list their entries or extract selected entries into a temporary directory (64 MiB cap) that can then be read with read_file or grep. Unsafe paths and symlinks are skipped.".to_string(),
+                parameters,
+                required: vec!["archive_path".to_string()],
+            };
+
+            Self {
+                tool,
+                extract_root: std::env::temp_dir(),
+            }
+        }
+
+        fn list(&self, path: &Path, format: Format) -> Result<String, Box<dyn Error>> {
+            let mut lines = Vec::new();
+            let mut count = 0usize;
+            let mut total = 0u64;
+            walk(path, format, &mut |entry, _| {
+                count += 1;
+                total += entry.size;
+                if lines.len() < MAX_LISTED_ENTRIES {
+                    lines.push(match &entry.kind {
+                        EntryKind::File => format!("{:>12}  {}", entry.size, entry.name),
+                        EntryKind::Dir => format!("{:>12}  {}/", "", entry.name),
+                        EntryKind::Symlink(target) => {
+                            format!("{:>12}  {} -> {}", "", entry.name, target)
+                        }
+                        EntryKind::Other => format!("{:>12}  {} (special)", "", entry.name),
+                    });
+                }
+                Ok(true)
+            })?;
+
+            let mut out = format!(
+                "{} ({}): {} entries, {} bytes uncompressed\n{}",
+                path.display(),
+                format.label(),
+                count,
+                total,
+                lines.join("\n")
+            );
+            if count > lines.len() {
+                out.push_str(&format!(
+                    "\n... {} more entries not shown; extract with entries=[...] globs to narrow down",
+                    count - lines.len()
+                ));
+            }
+            Ok(out)
+        }
+
+        fn extract(
+            &self,
+            path: &Path,
+            format: Format,
+            patterns: &[String],
+        ) -> Result<String, Box<dyn Error>> {
+            let stem = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("archive")
+                .split('.')
+                .next()
+                .unwrap_or("archive")
+                .to_string();
+            let millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let dest = self.extract_root.join(format!(
+                "pengy-archive-{}-{}-{}",
+                stem,
+                std::process::id(),
+                millis
+            ));
+            fs::create_dir_all(&dest)?;
+
+            let mut matched = vec![false; patterns.len()];
+            let mut written = Vec::new();
+            let mut skipped = Vec::new();
+            let mut total = 0u64;
+            let mut capped = false;
+
+            walk(path, format, &mut |entry, reader| {
+                let hits: Vec<usize> = (0..patterns.len())
+                    .filter(|i| selects(&patterns[*i], &entry.name))
+                    .collect();
+                if !patterns.is_empty() && hits.is_empty() {
+                    return Ok(true);
+                }
+                for i in hits {
+                    matched[i] = true;
+                }
+
+                let Some(relative) = safe_relative(&entry.name) else {
+                    skipped.push(format!("{} (unsafe path)", entry.name));
+                    return Ok(true);
+                };
+                let target = dest.join(&relative);
+                match &entry.kind {
+                    EntryKind::Dir => {
+                        fs::create_dir_all(&target)?;
+                        Ok(true)
+                    }
+                    EntryKind::Symlink(link) => {
+                        skipped.push(format!("{} -> {} (symlink)", entry.name, link));
+                        Ok(true)
+                    }
+                    EntryKind::Other => {
+                        skipped.push(format!("{} (special file)", entry.name));
+                        Ok(true)
+                    }
+                    EntryKind::File => {
+                        if let Some(parent) = target.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        let remaining = MAX_EXTRACT_BYTES - total;
+                        let mut out = File::create(&target)?;
+                        let copied = io::copy(&mut reader.take(remaining + 1), &mut out)?;
+                        if copied > remaining {
+                            drop(out);
+                            let _ = fs::remove_file(&target);
+                            capped = true;
+                            return Ok(false);
+                        }
+                        total += copied;
+                        written.push((target, copied));
+                        Ok(true)
+                    }
+                }
+            })?;
+
+            if written.is_empty() && !capped {
+                let _ = fs::remove_dir_all(&dest);
+                if !patterns.is_empty() && !matched.iter().any(|m| *m) {
+                    return Err(format!(
+                        "No entries matched {:?}; use action=list to see the archive contents",
+                        patterns
+                    )
+                    .into());
+                }
+            }
+
+            let mut out = format!(
+                "Extracted {} file(s), {} bytes, to {}",
+                written.len(),
+                total,
+                dest.display()
+            );
+            for (target, size) in written.iter().take(MAX_LISTED_ENTRIES) {
+                out.push_str(&format!("\n- {} ({} bytes)", target.display(), size));
+            }
+            if written.len() > MAX_LISTED_ENTRIES {
+                out.push_str(&format!(
+                    "\n... {} more files",
+                    written.len() - MAX_LISTED_ENTRIES
+                ));
+            }
+            if capped {
+                out.push_str(&format!(
+                    "\nWarning: stopped at the {} byte extraction cap; select fewer entries to get the rest",
+                    MAX_EXTRACT_BYTES
+                ));
+            }
+            for note in &skipped {
+                out.push_str(&format!("\nSkipped: {}", note));
+            }
+            for (pattern, hit) in patterns.iter().zip(&matched) {
+                if !hit {
+                    out.push_str(&format!("\nWarning: no entry matched {:?}", pattern));
+                }
+            }
+            Ok(out)
+        }
+    }
+
+    impl ToolCall for ArchiveTool {
+        fn get_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+            self.tool.get_json()
+        }
+
+        fn run(&self, arguments: &str) -> Result<String, Box<dyn Error>> {
+            let args: serde_json::Value = serde_json::from_str(arguments)?;
+            let archive_path = args
+                .get("archive_path")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: archive_path")?;
+            let action = args
+                .get("action")
+                .and_then(|v| v.as_str())
+                .unwrap_or("list");
+            let patterns: Vec<String> = args
+                .get("entries")
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let path = Path::new(archive_path);
+            if !path.is_file() {
+                return Err(format!("Archive not found: {}", archive_path).into());
+            }
+            let format = detect_format(path)?;
+
+            match action {
+                "list" => self.list(path, format),
+                "extract" => self.extract(path, format, &patterns),
+                other => Err(format!("Unknown action '{}': use 'list' or 'extract'", other).into()),
+            }
+        }
+
+        fn name(&self) -> &str {
+            "archive"
+        }
+    }
+
+    fn detect_format(path: &Path) -> Result<Format, Box<dyn Error>> {
+        let mut head = [0u8; 512];
+        let mut file = File::open(path)?;
+        let mut read = 0;
+        while read < head.len() {
+            let n = file.read(&mut head[read..])?;
+            if n == 0 {
+                break;
+            }
+            read += n;
+        }
+        let head = &head[..read];
+
+        if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+            return Ok(Format::Zip);
+        }
+        if head.starts_with(&[0x1f, 0x8b]) {
+            return Ok(Format::TarGz);
+        }
+        if head.len() >= 262 && &head[257..262] == b"ustar" {
+            return Ok(Format::Tar);
+        }
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        if name.ends_with(".tar") {
+            return Ok(Format::Tar);
+        }
+        Err(format!("{} is not a zip, tar or tar.gz archive", path.display()).into())
+    }
+
+    fn walk(path: &Path, format: Format, visit: &mut Visitor) -> Result<(), Box<dyn Error>> {
+        match format {
+            Format::Zip => walk_zip(path, visit),
+            Format::Tar => walk_tar(io::BufReader::new(File::open(path)?), visit),
+            Format::TarGz => walk_tar(GzDecoder::new(io::BufReader::new(File::open(path)?)), visit),
+        }
+    }
+
+    /// Walk a ustar/GNU/PAX tar stream. Long names and PAX headers are read
+    /// here rather than by the `tar` crate so their size can be capped.
+    fn walk_tar(reader: impl Read, visit: &mut Visitor) -> Result<(), Box<dyn Error>> {
+        let mut archive = tar::Archive::new(reader);
+        let mut long_name: Option<String> = None;
+        let mut long_link: Option<String> = None;
+        for entry in archive.entries()?.raw(true) {
+            let mut entry = entry?;
+            let entry_type = entry.header().entry_type();
+
+            // GNU long names and PAX headers describe the next entry
+            let is_metadata = entry_type.is_gnu_longname()
+                || entry_type.is_gnu_longlink()
+                || entry_type.is_pax_local_extensions()
+                || entry_type.is_pax_global_extensions();
+            if is_metadata {
+                if entry.size() > MAX_METADATA_BYTES {
+                    return Err(format!(
+                        "tar metadata entry of {} bytes exceeds the {} byte limit",
+                        entry.size(),
+                        MAX_METADATA_BYTES
+                    )
+                    .into());
+                }
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data)?;
+                let text = || {
+                    String::from_utf8_lossy(&data)
+                        .trim_end_matches('\0')
+                        .to_string()
+                };
+                if entry_type.is_gnu_longname() {
+                    long_name = Some(text());
+                } else if entry_type.is_gnu_longlink() {
+                    long_link = Some(text());
+                } else if entry_type.is_pax_local_extensions() {
+                    for extension in tar::PaxExtensions::new(&data).flatten() {
+                        match extension.key() {
+                            Ok("path") => {
+                                long_name = Some(extension.value()?.to_string());
+                            }
+                            Ok("linkpath") => {
+                                long_link = Some(extension.value()?.to_string());
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let name = match long_name.take() {
+                Some(name) => name,
+                None => String::from_utf8_lossy(&entry.path_bytes()).to_string(),
+            };
+            let link = match long_link.take() {
+                Some(link) => link,
+                None => entry
+                    .link_name_bytes()
+                    .map(|b| String::from_utf8_lossy(&b).to_string())
+                    .unwrap_or_default(),
+            };
+            let kind = if entry_type.is_file() || entry_type.is_contiguous() {
+                EntryKind::File
+            } else if entry_type.is_dir() {
+                EntryKind::Dir
+            } else if entry_type.is_symlink() {
+                EntryKind::Symlink(link)
+            } else {
+                EntryKind::Other
+            };
+            let entry_info = Entry {
+                name: name.trim_end_matches('/').to_string(),
+                size: if kind == EntryKind::File {
+                    entry.size()
+                } else {
+                    0
+                },
+                kind,
+            };
+            if !visit(&entry_info, &mut entry)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn walk_zip(path: &Path, visit: &mut Visitor) -> Result<(), Box<dyn Error>> {
+        let mut archive = zip::ZipArchive::new(io::BufReader::new(File::open(path)?))?;
+        for index in 0..archive.len() {
+            // Read the metadata without decompressing, so encrypted entries and
+            // unsupported compression methods are listed instead of failing
+            let (name, size, kind) = {
+                let file = archive.by_index_raw(index)?;
+                let name = file.name().replace('\\', "/");
+                let kind = if file.is_dir() {
+                    EntryKind::Dir
+                } else if file.encrypted()
+                    || !matches!(
+                        file.compression(),
+                        zip::CompressionMethod::Stored | zip::CompressionMethod::Deflated
+                    )
+                {
+                    EntryKind::Other
+                } else if file.is_symlink() {
+                    EntryKind::Symlink(String::new())
+                } else {
+                    EntryKind::File
+                };
+                (name, file.size(), kind)
+            };
+
+            let keep_going = match kind {
+                EntryKind::File => {
+                    let entry = Entry {
+                        name: name.trim_end_matches('/').to_string(),
+                        size,
+                        kind,
+                    };
+                    visit(&entry, &mut archive.by_index(index)?)?
+                }
+                EntryKind::Symlink(_) => {
+                    let mut target = String::new();
+                    archive
+                        .by_index(index)?
+                        .take(MAX_METADATA_BYTES)
+                        .read_to_string(&mut target)?;
+                    let entry = Entry {
+                        name: name.trim_end_matches('/').to_string(),
+                        size: 0,
+                        kind: EntryKind::Symlink(target),
+                    };
+                    visit(&entry, &mut io::empty())?
+                }
+                kind => {
+                    let entry = Entry {
+                        name: name.trim_end_matches('/').to_string(),
+                        size: 0,
+                        kind,
+                    };
+                    visit(&entry, &mut io::empty())?
+                }
+            };
+            if !keep_going {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an extraction pattern selects `name`: an exact name, a
+    /// directory prefix or a glob.
+    fn selects(pattern: &str, name: &str) -> bool {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        name == pattern
+            || name.starts_with(&format!("{}/", pattern))
+            || workspace_files::matches_glob(name, pattern)
+    }
+
+    /// Entry name as a path that stays inside the extraction directory.
+    fn safe_relative(name: &str) -> Option<PathBuf> {
+        let mut out = PathBuf::new();
+        for component in Path::new(name).components() {
+            match component {
+                Component::Normal(part) => out.push(part),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        (!out.as_os_str().is_empty()).then_some(out)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        fn append_tar(
+            builder: &mut tar::Builder<Vec<u8>>,
+            name: &str,
+            data: &[u8],
+            kind: tar::EntryType,
+        ) {
+            let mut header = tar::Header::new_ustar();
+            header.as_mut_bytes()[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(data.len() as u64);
+            header.set_entry_type(kind);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, data).unwrap();
+        }
+
+        fn build_tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+            let mut builder = tar::Builder::new(Vec::new());
+            for (name, data) in entries {
+                append_tar(&mut builder, name, data, tar::EntryType::Regular);
+            }
+            builder.into_inner().unwrap()
+        }
+
+        fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+            let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated);
+            for (name, data) in entries {
+                writer.start_file(*name, options).unwrap();
+                writer.write_all(data).unwrap();
+            }
+            writer.finish().unwrap().into_inner()
+        }
+
+        fn tool_in(dir: &Path) -> ArchiveTool {
+            let mut tool = ArchiveTool::new();
+            tool.extract_root = dir.to_path_buf();
+            tool
+        }
+
+        #[test]
+        fn test_list_and_extract_tar_gz() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let tar = build_tar(&[
+                ("pkg/src/lib.rs", b"pub fn a() {}\n"),
+                ("pkg/README.md", b"# pkg\n"),
+            ]);
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&tar).unwrap();
+            let archive = dir.path().join("pkg-1.0.tar.gz");
+            fs::write(&archive, encoder.finish().unwrap()).unwrap();
+            let tool = tool_in(dir.path());
+
+            let args = serde_json::json!({"archive_path": archive});
+            let listing = tool.run(&args.to_string()).unwrap();
+            assert!(listing.contains("tar.gz): 2 entries"), "{}", listing);
+            assert!(listing.contains("pkg/src/lib.rs"));
+
+            let args = serde_json::json!({
+                "archive_path": archive,
+                "action": "extract",
+                "entries": ["*.rs"]
+            });
+            let result = tool.run(&args.to_string()).unwrap();
+            assert!(result.contains("Extracted 1 file(s)"), "{}", result);
+            let dest = fs::read_dir(dir.path())
+                .unwrap()
+                .flatten()
+                .find(|e| e.path().is_dir())
+                .unwrap()
+                .path();
+            assert_eq!(
+                fs::read_to_string(dest.join("pkg/src/lib.rs")).unwrap(),
+                "pub fn a() {}\n"
+            );
+            assert!(!dest.join("pkg/README.md").exists());
+        }
+
+        #[test]
+        fn test_zip_extract_skips_unsafe_paths() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let archive = dir.path().join("bundle.zip");
+            fs::write(
+                &archive,
+                build_zip(&[("data/a.txt", b"hello zip"), ("../evil.txt", b"nope")]),
+            )
+            .unwrap();
+            let tool = tool_in(dir.path());
+
+            let args = serde_json::json!({"archive_path": archive, "action": "extract"});
+            let result = tool.run(&args.to_string()).unwrap();
+            assert!(
+                result.contains("Extracted 1 file(s), 9 bytes"),
+                "{}",
+                result
+            );
+            assert!(result.contains("Skipped: ../evil.txt (unsafe path)"));
+            assert!(!dir.path().join("evil.txt").exists());
+        }
+
+        #[test]
+        fn test_tar_rejects_oversized_metadata() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let mut builder = tar::Builder::new(Vec::new());
+            let pax = vec![b'a'; MAX_METADATA_BYTES as usize + 1];
+            append_tar(
+                &mut builder,
+                "././@PaxHeader",
+                &pax,
+                tar::EntryType::XHeader,
+            );
+            append_tar(&mut builder, "a.txt", b"a", tar::EntryType::Regular);
+            let tar = builder.into_inner().unwrap();
+            let archive = dir.path().join("big.tar");
+            fs::write(&archive, tar).unwrap();
+            let tool = tool_in(dir.path());
+
+            let args = serde_json::json!({"archive_path": archive});
+            let err = tool.run(&args.to_string()).unwrap_err().to_string();
+            assert!(err.contains("exceeds the 65536 byte limit"), "{}", err);
+        }
+    }
+}
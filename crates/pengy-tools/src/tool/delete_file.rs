@@ -1,11 +1,14 @@
 pub mod delete_file {
     //! Delete a file (or directory) within the current workspace, with safety
     //! checks to avoid accidentally removing paths outside the project.
+    //! Deleted paths are moved into the session trash so `restore_file` can
+    //! bring them back; paths too large for the trash are deleted permanently
+    //! and the result says so.
     use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use crate::util::trash::trash;
     use serde_json;
     use std::collections::HashMap;
     use std::error::Error;
-    use std::fs;
     use std::path::PathBuf;
 
     /// Deletes files or directories after validating they are inside the
@@ -34,7 +37,7 @@ pub mod delete_file {
 
             let tool = Tool {
                 name: "delete_file".to_string(),
-                description: "Delete a file or directory inside the workspace after validation."
+                description: "Delete a file or directory inside the workspace after validation. Deleted paths go to the session trash and can be brought back with restore_file."
                     .to_string(),
                 parameters,
                 required: vec!["target_file".to_string()],
@@ -90,12 +93,21 @@ pub mod delete_file {
                 return Err(format!("Path does not exist: {}", path.display()).into());
             }
 
-            if path.is_dir() {
-                fs::remove_dir_all(&path)?;
-                Ok(format!("Directory deleted: {}", path.display()))
-            } else {
-                fs::remove_file(&path)?;
-                Ok(format!("File deleted: {}", path.display()))
+            let is_dir = std::fs::symlink_metadata(&path).is_ok_and(|m| m.is_dir());
+            let kind = if is_dir { "Directory" } else { "File" };
+            match trash::move_to_trash(&self.workspace_root, &path, "deleted")? {
+                Some(entry) => Ok(format!(
+                    "{} deleted: {} (moved to trash as {}; use restore_file to undo)",
+                    kind,
+                    path.display(),
+                    entry.id
+                )),
+                None => Ok(format!(
+                    "{} deleted: {} (warning: larger than the {} MB trash limit, so it was deleted permanently and cannot be restored)",
+                    kind,
+                    path.display(),
+                    trash::MAX_ENTRY_BYTES / (1024 * 1024)
+                )),
             }
         }
 
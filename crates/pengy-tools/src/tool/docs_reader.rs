@@ -83,31 +83,32 @@ pub mod docs_reader {
             }
 
             // Fallback: try pdf-extract library
-            match pdf_extract::extract_text(file_path.as_path()) {
-                Ok(text) => {
-                    if !text.trim().is_empty() {
-                        return Ok(text);
-                    }
-                }
-                Err(e) => {
-                    // If pdf-extract fails, try pdftotext command as another fallback
-                    if let Ok(output) = Command::new("pdftotext")
-                        .arg(file_path.as_os_str())
-                        .arg("-")
-                        .output()
-                    {
-                        if output.status.success() {
-                            let text = String::from_utf8_lossy(&output.stdout).to_string();
-                            if !text.trim().is_empty() {
-                                return Ok(text);
-                            }
-                        }
-                    }
-                    return Err(format!("Failed to extract text from PDF: {}. Tried pandoc, pdf-extract library, and pdftotext command.", e).into());
+            #[cfg(feature = "pdf")]
+            let library_error = match pdf_extract::extract_text(file_path.as_path()) {
+                Ok(text) if !text.trim().is_empty() => return Ok(text),
+                Ok(_) => None,
+                Err(e) => Some(e.to_string()),
+            };
+            #[cfg(not(feature = "pdf"))]
+            let library_error = Some("built without the pdf feature".to_string());
+
+            // Then the pdftotext command as another fallback
+            if let Ok(output) = Command::new("pdftotext")
+                .arg(file_path.as_os_str())
+                .arg("-")
+                .output()
+                && output.status.success()
+            {
+                let text = String::from_utf8_lossy(&output.stdout).to_string();
+                if !text.trim().is_empty() {
+                    return Ok(text);
                 }
             }
 
-            Err("Failed to extract text from PDF: All conversion methods failed or returned empty content.".into())
+            match library_error {
+                Some(e) => Err(format!("Failed to extract text from PDF: {}. Tried pandoc, pdf-extract library, and pdftotext command.", e).into()),
+                None => Err("Failed to extract text from PDF: All conversion methods failed or returned empty content.".into()),
+            }
         }
 
         /// Apply either word-count or line-count truncation to the extracted
@@ -1,13 +1,16 @@
 pub mod edit {
     //! Perform targeted string replacements within files using a series of
     //! fallback matching strategies to handle whitespace, casing, and line
-    //! ending differences.
+    //! ending differences. The file's encoding and line endings are preserved
+    //! when it is written back, and the replacement text is re-indented to the
+    //! file's detected conventions.
 
     use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use crate::util::conventions::conventions::Conventions;
+    use crate::util::text_file::text_file::{self, TextFile};
     use serde_json;
     use std::collections::HashMap;
     use std::error::Error;
-    use std::fs;
     use std::path::Path;
 
     /// Performs resilient in-file replacements using multiple match strategies
@@ -416,8 +419,15 @@ pub mod edit {
                 return Err(format!("File does not exist: {}", file_path).into());
             }
 
-            // Read file content
-            let content = fs::read_to_string(file_path)?;
+            // Read file content, normalized to LF so matching is independent of
+            // the file's line ending style
+            let file = TextFile::read(path)?;
+            let content = file.content.clone();
+            let old_string = &text_file::normalize_line_endings(old_string);
+            let conventions = Conventions::detect(path, Some(&content));
+            let (new_string, style_warnings) =
+                conventions.conform(&text_file::normalize_line_endings(new_string));
+            let new_string = &new_string;
 
             if replace_all {
                 // Replace all occurrences
@@ -446,20 +456,27 @@ pub mod edit {
                     );
                 }
 
-                fs::write(file_path, &modified_content)?;
-                Ok(format!(
-                    "Successfully replaced {} occurrence(s) in {}",
-                    replacements, file_path
+                let mut warnings = file.write(path, &modified_content)?;
+                warnings.extend(style_warnings);
+                Ok(text_file::annotate(
+                    format!(
+                        "Successfully replaced {} occurrence(s) in {}",
+                        replacements, file_path
+                    ),
+                    &file,
+                    &warnings,
                 ))
             } else {
                 // Replace first occurrence only
                 if let Some((start, end)) = Self::find_match_with_fallbacks(&content, old_string) {
                     let mut modified_content = content;
                     modified_content.replace_range(start..end, new_string);
-                    fs::write(file_path, &modified_content)?;
-                    Ok(format!(
-                        "Successfully replaced first occurrence in {}",
-                        file_path
+                    let mut warnings = file.write(path, &modified_content)?;
+                    warnings.extend(style_warnings);
+                    Ok(text_file::annotate(
+                        format!("Successfully replaced first occurrence in {}", file_path),
+                        &file,
+                        &warnings,
                     ))
                 } else {
                     Err(format!("No match found for oldString in file: {}", file_path).into())
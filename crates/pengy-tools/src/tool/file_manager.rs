@@ -1,6 +1,12 @@
 pub mod file_manager {
     //! Create files or directories within the current workspace while enforcing
-    //! path safety and optional partial replacements.
+    //! path safety and optional partial replacements. Rewrites of existing files
+    //! keep their encoding, line endings and permissions, and a `mode` can be
+    //! applied chmod-style (adding an execute bit requires user approval).
+    //! The original of every rewritten file is snapshotted into the session
+    //! trash first, so `restore_file` can undo overwrites and truncations.
+    //! Binary assets can be written from base64 content (`encoding: "base64"`),
+    //! capped in size and requiring approval above a threshold.
 
     use serde_json;
     use std::collections::HashMap;
@@ -8,7 +14,17 @@ pub mod file_manager {
     use std::fs;
     use std::path::{Component, Path, PathBuf};
 
+    use crate::tool::approval::approval;
     use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use crate::util::conventions::conventions::Conventions;
+    use crate::util::file_write::file_write;
+    use crate::util::text_file::text_file::{self, TextFile};
+    use crate::util::trash::trash;
+
+    /// Largest binary file that can be written from base64 content.
+    const MAX_BINARY_BYTES: usize = 2 * 1024 * 1024;
+    /// Binary writes above this size need user approval.
+    const BINARY_APPROVAL_BYTES: usize = 256 * 1024;
 
     /// Tool for creating files or folders within the current workspace.
     /// Use this instead of bash when you need to scaffold paths or seed file contents.
@@ -61,6 +77,17 @@ pub mod file_manager {
                 },
             );
 
+            let mut encoding_items = HashMap::new();
+            encoding_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "encoding".to_string(),
+                Parameter {
+                    items: encoding_items,
+                    description: "How content is encoded: 'text' (default) or 'base64' for binary files such as images, icons or fixtures. Base64 content (a data: URL prefix is accepted) is decoded and written byte for byte; startLine/endLine are not supported. Limited to 2 MiB, and files over 256 KiB need user approval.".to_string(),
+                    enum_values: Some(vec!["text".to_string(), "base64".to_string()]),
+                },
+            );
+
             let mut start_line_items = HashMap::new();
             start_line_items.insert("type".to_string(), "number".to_string());
             parameters.insert(
@@ -106,6 +133,17 @@ pub mod file_manager {
                 },
             );
 
+            let mut mode_items = HashMap::new();
+            mode_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "mode".to_string(),
+                Parameter {
+                    items: mode_items,
+                    description: "Optional permission change, chmod-style: octal ('755', '0644') or symbolic ('+x', 'u+x', 'go-w'). Applied after writing; with no content on an existing path only the mode is changed. Adding an execute bit requires user approval.".to_string(),
+                    enum_values: None,
+                },
+            );
+
             // Add files parameter for batch operations
             let mut files_items = HashMap::new();
             files_items.insert("type".to_string(), "array".to_string());
@@ -114,14 +152,14 @@ pub mod file_manager {
                 "files".to_string(),
                 Parameter {
                     items: files_items,
-                    description: "Array of file operations to perform. Each object should have: path (required, use full absolute path), kind (optional, default 'file'), content (optional), startLine/endLine (optional, for partial replacement - you MUST know line numbers), overwrite (optional, default false), createParents (optional, default true). Use this for batch operations to modify multiple files at once.".to_string(),
+                    description: "Array of file operations to perform. Each object should have: path (required, use full absolute path), kind (optional, default 'file'), content (optional), startLine/endLine (optional, for partial replacement - you MUST know line numbers), overwrite (optional, default false), createParents (optional, default true), mode (optional), encoding (optional, 'base64' for binary content). Use this for batch operations to modify multiple files at once.".to_string(),
                     enum_values: None,
                 },
             );
 
             let tool = Tool {
                 name: "file_manager".to_string(),
-                description: "Create files or folders inside the current workspace. Supports single file operations (use 'path') or batch operations (use 'files' array). IMPORTANT: To modify only part of an existing file, you MUST provide startLine and endLine parameters along with content. Without line numbers, the entire file will be replaced. Always use full absolute paths. Use grep or read the file first to determine the exact line numbers you need to modify.".to_string(),
+                description: "Create files or folders inside the current workspace. Supports single file operations (use 'path') or batch operations (use 'files' array). IMPORTANT: To modify only part of an existing file, you MUST provide startLine and endLine parameters along with content. Without line numbers, the entire file will be replaced. Always use full absolute paths. Use grep or read the file first to determine the exact line numbers you need to modify. Existing files keep their permissions; use 'mode' to chmod. Never write binary data as text: use encoding='base64'.".to_string(),
                 parameters,
                 required: vec![],
             };
@@ -220,17 +258,7 @@ pub mod file_manager {
             Ok(format!("Directory created at {}", path.display()))
         }
 
-        /// Write content to a file, optionally replacing only a specific line
-        /// range and creating parent directories when requested.
-        fn write_file(
-            &self,
-            path: &Path,
-            content: &str,
-            overwrite: bool,
-            create_parents: bool,
-            start_line: Option<usize>,
-            end_line: Option<usize>,
-        ) -> Result<String, Box<dyn Error>> {
+        fn prepare_parent(path: &Path, create_parents: bool) -> Result<(), Box<dyn Error>> {
             if let Some(parent) = path.parent() {
                 if create_parents {
                     fs::create_dir_all(parent)?;
@@ -242,6 +270,21 @@ pub mod file_manager {
                     .into());
                 }
             }
+            Ok(())
+        }
+
+        /// Write content to a file, optionally replacing only a specific line
+        /// range and creating parent directories when requested.
+        fn write_file(
+            &self,
+            path: &Path,
+            content: &str,
+            overwrite: bool,
+            create_parents: bool,
+            start_line: Option<usize>,
+            end_line: Option<usize>,
+        ) -> Result<String, Box<dyn Error>> {
+            Self::prepare_parent(path, create_parents)?;
 
             // If line numbers are provided, do partial replacement
             if let (Some(start), Some(end)) = (start_line, end_line) {
@@ -258,8 +301,8 @@ pub mod file_manager {
                 }
 
                 // Read existing file
-                let existing_content = fs::read_to_string(path)?;
-                let lines: Vec<&str> = existing_content.lines().collect();
+                let existing = TextFile::read(path)?;
+                let lines: Vec<&str> = existing.content.lines().collect();
 
                 // Validate line numbers (1-based to 0-based conversion)
                 if start < 1 || end < 1 {
@@ -286,8 +329,11 @@ pub mod file_manager {
                 // Lines before the replacement (0-based: start-1)
                 new_lines.extend_from_slice(&lines[..(start - 1)]);
 
-                // New content (split by lines)
-                let new_content_lines: Vec<&str> = content.lines().collect();
+                // New content (split by lines), indented like the rest of the file
+                let (normalized_content, style_warnings) =
+                    Conventions::detect(path, Some(&existing.content))
+                        .conform(&text_file::normalize_line_endings(content));
+                let new_content_lines: Vec<&str> = normalized_content.lines().collect();
                 new_lines.extend(new_content_lines);
 
                 // Lines after the replacement (0-based: end, which is exclusive, so we use end)
@@ -295,24 +341,34 @@ pub mod file_manager {
                     new_lines.extend_from_slice(&lines[end..]);
                 }
 
-                // Reconstruct file with original line endings
-                let new_content = if existing_content.contains("\r\n") {
-                    new_lines.join("\r\n")
-                } else if existing_content.contains('\r') {
-                    new_lines.join("\r")
-                } else {
-                    new_lines.join("\n")
-                };
+                // Reconstruct file, keeping the trailing newline if it had one;
+                // the original line endings and encoding are re-applied on write
+                let mut new_content = new_lines.join("\n");
+                if existing.content.ends_with('\n') {
+                    new_content.push('\n');
+                }
 
-                fs::write(path, new_content)?;
-                return Ok(format!(
-                    "Replaced lines {}-{} in {}",
-                    start,
-                    end,
-                    path.display()
+                let saved = trash::snapshot(&self.workspace_root, path, "lines replaced")?;
+                let mut warnings = existing.write(path, &new_content)?;
+                warnings.extend(style_warnings);
+                return Ok(text_file::annotate(
+                    format!(
+                        "Replaced lines {}-{} in {} ({})",
+                        start,
+                        end,
+                        path.display(),
+                        trash::snapshot_note(saved.as_ref())
+                    ),
+                    &existing,
+                    &warnings,
                 ));
             }
 
+            // Whole-file writes define their own style, so only configured
+            // conventions (EditorConfig, formatter settings) are applied
+            let (content, style_warnings) = Conventions::detect(path, None).conform(content);
+            let content = content.as_str();
+
             // Full file replacement (existing behavior)
             if path.exists() {
                 if path.is_dir() {
@@ -327,10 +383,140 @@ pub mod file_manager {
                     )
                     .into());
                 }
+
+                let reason = if content.is_empty() {
+                    "truncated"
+                } else {
+                    "overwritten"
+                };
+                let saved = trash::snapshot(&self.workspace_root, path, reason)?;
+                let message = format!(
+                    "File written at {} ({})",
+                    path.display(),
+                    trash::snapshot_note(saved.as_ref())
+                );
+
+                // Keep the existing file's encoding and line endings when it is
+                // a text file we can decode; otherwise write the content as-is
+                if let Ok(existing) = TextFile::read(path) {
+                    let mut warnings = existing.write(path, content)?;
+                    warnings.extend(style_warnings);
+                    return Ok(text_file::annotate(message, &existing, &warnings));
+                }
+                let file = TextFile::for_new_file(path, content);
+                let mut warnings = file.write(path, content)?;
+                warnings.extend(style_warnings);
+                return Ok(text_file::annotate(message, &file, &warnings));
+            }
+
+            let file = TextFile::for_new_file(path, content);
+            let mut warnings = file.write(path, content)?;
+            warnings.extend(style_warnings);
+            Ok(text_file::annotate(
+                format!("File written at {}", path.display()),
+                &file,
+                &warnings,
+            ))
+        }
+
+        /// Decode base64 `content` and write it byte for byte, enforcing the
+        /// size limit and asking for approval for large files.
+        fn write_binary(
+            &self,
+            path: &Path,
+            content: &str,
+            overwrite: bool,
+            create_parents: bool,
+        ) -> Result<String, Box<dyn Error>> {
+            let bytes = decode_base64(content)?;
+            if bytes.len() > MAX_BINARY_BYTES {
+                return Err(format!(
+                    "Binary content is {} bytes, more than the {} byte limit",
+                    bytes.len(),
+                    MAX_BINARY_BYTES
+                )
+                .into());
+            }
+
+            if path.is_dir() {
+                return Err(format!("Path is a directory, not a file: {}", path.display()).into());
+            }
+            if path.exists() && !overwrite {
+                return Err(format!(
+                    "File already exists: {} (set overwrite=true to replace it)",
+                    path.display()
+                )
+                .into());
+            }
+
+            let kind = sniff_binary(&bytes);
+            if bytes.len() > BINARY_APPROVAL_BYTES {
+                let action = format!(
+                    "write {} bytes of binary data ({}) to {}",
+                    bytes.len(),
+                    kind,
+                    path.display()
+                );
+                if !approval::request("file_manager", &action) {
+                    return Err(format!(
+                        "Binary write denied: files over {} bytes require user approval",
+                        BINARY_APPROVAL_BYTES
+                    )
+                    .into());
+                }
+            }
+
+            Self::prepare_parent(path, create_parents)?;
+            let mut message = format!(
+                "Binary file written at {} ({} bytes, {})",
+                path.display(),
+                bytes.len(),
+                kind
+            );
+            if path.exists() {
+                let saved = trash::snapshot(&self.workspace_root, path, "overwritten")?;
+                message.push_str(&format!(" ({})", trash::snapshot_note(saved.as_ref())));
+            }
+            file_write::write_preserving(path, &bytes)?;
+            Ok(message)
+        }
+
+        /// Apply a chmod-style `spec` to `path`, asking for approval before
+        /// any execute bit is added.
+        fn change_mode(&self, path: &Path, spec: &str) -> Result<String, Box<dyn Error>> {
+            let old_mode = file_write::current_mode(path)?;
+            let new_mode = file_write::parse_mode(spec, old_mode)?;
+            if new_mode == old_mode {
+                return Ok(format!(
+                    "Mode of {} unchanged ({:04o})",
+                    path.display(),
+                    old_mode
+                ));
+            }
+
+            if file_write::adds_execute(old_mode, new_mode) {
+                let action = format!(
+                    "make {} executable (mode {:04o} -> {:04o})",
+                    path.display(),
+                    old_mode,
+                    new_mode
+                );
+                if !approval::request("file_manager", &action) {
+                    return Err(format!(
+                        "Permission change denied: making {} executable requires user approval",
+                        path.display()
+                    )
+                    .into());
+                }
             }
 
-            fs::write(path, content)?;
-            Ok(format!("File written at {}", path.display()))
+            file_write::set_mode(path, new_mode)?;
+            Ok(format!(
+                "Mode of {} changed from {:04o} to {:04o}",
+                path.display(),
+                old_mode,
+                new_mode
+            ))
         }
 
         fn process_single_file(
@@ -373,6 +559,34 @@ pub mod file_manager {
                 return Err("Both startLine and endLine must be provided together for partial file replacement".into());
             }
 
+            let mode = file_op.get("mode").and_then(|v| v.as_str());
+
+            let binary = match file_op.get("encoding").and_then(|v| v.as_str()) {
+                None | Some("text") | Some("utf-8") => false,
+                Some("base64") => true,
+                Some(other) => {
+                    return Err(format!(
+                        "Unsupported encoding '{}': use 'text' or 'base64'",
+                        other
+                    )
+                    .into());
+                }
+            };
+            if binary && start_line.is_some() {
+                return Err("startLine/endLine cannot be used with encoding=base64".into());
+            }
+
+            let target_path = self.resolve_path(raw_path)?;
+
+            // A mode without content on an existing path is a pure chmod
+            if let Some(spec) = mode
+                && file_op.get("content").is_none()
+                && start_line.is_none()
+                && target_path.exists()
+            {
+                return self.change_mode(&target_path, spec);
+            }
+
             // Handle content - can be string, array, or object - convert to string
             let content = file_op
                 .get("content")
@@ -388,10 +602,14 @@ pub mod file_manager {
                 })
                 .unwrap_or_default();
 
-            let target_path = self.resolve_path(raw_path)?;
-
-            if kind == "directory" || kind == "folder" {
-                self.create_directory(&target_path, create_parents)
+            let result = if kind == "directory" || kind == "folder" {
+                self.create_directory(&target_path, create_parents)?
+            } else if binary {
+                let encoded = file_op
+                    .get("content")
+                    .and_then(|v| v.as_str())
+                    .ok_or("encoding=base64 requires content as a base64 string")?;
+                self.write_binary(&target_path, encoded, overwrite, create_parents)?
             } else {
                 self.write_file(
                     &target_path,
@@ -400,11 +618,68 @@ pub mod file_manager {
                     create_parents,
                     start_line,
                     end_line,
-                )
+                )?
+            };
+
+            match mode {
+                Some(spec) => match self.change_mode(&target_path, spec) {
+                    Ok(mode_result) => Ok(format!("{}\n{}", result, mode_result)),
+                    Err(e) => {
+                        Err(format!("{}, but the mode was not applied: {}", result, e).into())
+                    }
+                },
+                None => Ok(result),
             }
         }
     }
 
+    /// Decode standard or URL-safe base64, ignoring whitespace and an
+    /// optional `data:<mime>;base64,` prefix.
+    fn decode_base64(content: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        use base64::Engine;
+        use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+
+        let payload = match content.trim_start().strip_prefix("data:") {
+            Some(rest) => rest
+                .split_once(',')
+                .map(|(_, data)| data)
+                .ok_or("Malformed data URL: missing ',' before the base64 payload")?,
+            None => content,
+        };
+        let compact: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+        let unpadded = compact.trim_end_matches('=');
+        let padded = format!("{}{}", unpadded, "=".repeat((4 - unpadded.len() % 4) % 4));
+        STANDARD
+            .decode(&padded)
+            .or_else(|_| URL_SAFE.decode(&padded))
+            .map_err(|e| format!("Invalid base64 content: {}", e).into())
+    }
+
+    /// Name the format of well-known binary files from their magic bytes.
+    fn sniff_binary(bytes: &[u8]) -> &'static str {
+        const SIGNATURES: &[(&[u8], &str)] = &[
+            (b"\x89PNG\r\n\x1a\n", "PNG image"),
+            (b"\xff\xd8\xff", "JPEG image"),
+            (b"GIF87a", "GIF image"),
+            (b"GIF89a", "GIF image"),
+            (b"\x00\x00\x01\x00", "ICO icon"),
+            (b"%PDF-", "PDF document"),
+            (b"PK\x03\x04", "ZIP archive"),
+            (b"\x1f\x8b", "gzip data"),
+            (b"\x00asm", "WebAssembly module"),
+            (b"wOF2", "WOFF2 font"),
+            (b"wOFF", "WOFF font"),
+        ];
+        if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            return "WebP image";
+        }
+        SIGNATURES
+            .iter()
+            .find(|(magic, _)| bytes.starts_with(magic))
+            .map(|(_, kind)| *kind)
+            .unwrap_or("binary data")
+    }
+
     impl ToolCall for FileManagerTool {
         fn get_json(&self) -> Result<serde_json::Value, serde_json::Error> {
             self.tool.get_json()
@@ -1013,4 +1288,158 @@ mod tests {
         );
         assert!(result.unwrap_err().to_string().contains("out of range"));
     }
+
+    #[test]
+    fn test_line_based_replacement_preserves_crlf_and_bom() {
+        let (tool, temp_dir) = create_test_tool();
+        let file_path = temp_dir.path().join("windows.txt");
+        fs::write(&file_path, b"\xEF\xBB\xBFLine 1\r\nLine 2\r\nLine 3\r\n").unwrap();
+
+        let args = format!(
+            r#"{{
+                "path": "{}",
+                "content": "New Line 2",
+                "startLine": 2,
+                "endLine": 2
+            }}"#,
+            file_path.display()
+        );
+
+        let result = tool.run(&args);
+        assert!(result.is_ok(), "Should replace line: {:?}", result);
+        assert_eq!(
+            fs::read(&file_path).unwrap(),
+            b"\xEF\xBB\xBFLine 1\r\nNew Line 2\r\nLine 3\r\n"
+        );
+    }
+
+    #[test]
+    fn test_overwrite_preserves_crlf() {
+        let (tool, temp_dir) = create_test_tool();
+        let file_path = temp_dir.path().join("crlf.txt");
+        fs::write(&file_path, "old\r\ncontent\r\n").unwrap();
+
+        let args = r#"{
+                "path": "crlf.txt",
+                "content": "new\ncontent\n",
+                "overwrite": true
+            }"#;
+
+        let result = tool.run(args);
+        assert!(result.is_ok(), "Should overwrite file: {:?}", result);
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "new\r\ncontent\r\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_overwrite_preserves_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (tool, temp_dir) = create_test_tool();
+        let file_path = temp_dir.path().join("run.sh");
+        fs::write(&file_path, "echo old\n").unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let args = r#"{
+                "path": "run.sh",
+                "content": "echo new\n",
+                "overwrite": true
+            }"#;
+
+        let result = tool.run(args);
+        assert!(result.is_ok(), "Should overwrite file: {:?}", result);
+        let mode = fs::metadata(&file_path).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode, 0o755);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chmod_without_execute_needs_no_approval() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (tool, temp_dir) = create_test_tool();
+        let file_path = temp_dir.path().join("secret.txt");
+        fs::write(&file_path, "token").unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let result = tool.run(r#"{"path": "secret.txt", "mode": "go-r"}"#);
+        assert!(result.is_ok(), "Should change mode: {:?}", result);
+        let mode = fs::metadata(&file_path).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode, 0o600);
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "token");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chmod_plus_x_denied_without_approval() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (tool, temp_dir) = create_test_tool();
+        let file_path = temp_dir.path().join("tool.sh");
+        fs::write(&file_path, "echo hi\n").unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let result = tool.run(r#"{"path": "tool.sh", "mode": "+x"}"#);
+        assert!(result.is_err(), "Adding +x should require approval");
+        let mode = fs::metadata(&file_path).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode, 0o644);
+    }
+
+    #[test]
+    fn test_overwrite_snapshots_original_for_restore() {
+        use crate::tool::restore_file::restore_file::RestoreFileTool;
+
+        let (tool, temp_dir) = create_test_tool();
+        let file_path = temp_dir.path().join("config.toml");
+        fs::write(&file_path, "answer = 42\n").unwrap();
+
+        let args = r#"{"path": "config.toml", "content": "", "overwrite": true}"#;
+        let result = tool.run(args).unwrap();
+        assert!(result.contains("original saved to trash"), "{}", result);
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "");
+
+        let mut restore = RestoreFileTool::new();
+        restore.workspace_root = temp_dir.path().to_path_buf();
+        let listing = restore.run(r#"{"list": true}"#).unwrap();
+        assert!(listing.contains("[truncated]"), "{}", listing);
+
+        let result = restore.run(r#"{"target_file": "config.toml"}"#);
+        assert!(result.is_ok(), "Should restore file: {:?}", result);
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "answer = 42\n");
+    }
+
+    #[test]
+    fn test_write_binary_from_base64() {
+        let (tool, temp_dir) = create_test_tool();
+        // 1x1 transparent PNG, wrapped as a data URL with a line break
+        let png = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk\nYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+        let args = serde_json::json!({
+            "path": "assets/pixel.png",
+            "content": png,
+            "encoding": "base64"
+        });
+        let result = tool.run(&args.to_string()).unwrap();
+        assert!(result.contains("PNG image"), "{}", result);
+
+        let bytes = fs::read(temp_dir.path().join("assets/pixel.png")).unwrap();
+        assert_eq!(bytes.len(), 70);
+        assert!(bytes.starts_with(b"\x89PNG"));
+    }
+
+    #[test]
+    fn test_write_binary_rejects_invalid_and_oversized_content() {
+        let (tool, temp_dir) = create_test_tool();
+        let args = r#"{"path": "bad.bin", "content": "not base64!", "encoding": "base64"}"#;
+        assert!(tool.run(args).is_err());
+
+        use base64::Engine;
+        let large = base64::engine::general_purpose::STANDARD.encode(vec![0u8; 3 * 1024 * 1024]);
+        let args = serde_json::json!({"path": "big.bin", "content": large, "encoding": "base64"});
+        let err = tool.run(&args.to_string()).unwrap_err().to_string();
+        assert!(err.contains("limit"), "{}", err);
+        assert!(!temp_dir.path().join("big.bin").exists());
+    }
 }
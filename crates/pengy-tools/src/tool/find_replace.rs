@@ -0,0 +1,638 @@
+pub mod find_replace {
+    //! Simple find-and-replace tool that performs exact string substitutions
+    //! across an entire file.
+    //!
+    //! This is a lightweight alternative to the edit tool when you already know
+    //! the exact text to replace. It replaces all occurrences of the provided
+    //! search string within the target file. The file's encoding and line
+    //! endings are preserved.
+    //!
+    //! With `scope: "project"` the search runs over every file under a
+    //! directory. Matches are offered to the user as hunks grouped by file (the
+    //! TUI shows a picker; without a reviewer nothing is applied unless
+    //! `PENGY_AUTO_APPROVE=1`), and the accepted hunks are applied atomically:
+    //! files edited during the review abort the operation, the originals are
+    //! snapshotted into the session trash and, if any write fails, files
+    //! already written are rolled back.
+    use crate::tool::approval::approval::{self, SelectionItem};
+    use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use crate::util::conventions::conventions::Conventions;
+    use crate::util::file_write::file_write;
+    use crate::util::text_file::text_file::{self, TextFile};
+    use crate::util::trash::trash;
+    use crate::util::workspace_files::workspace_files;
+    use serde_json;
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Files larger than this are skipped in project mode.
+    const MAX_PROJECT_FILE_BYTES: u64 = 1024 * 1024;
+    /// Upper bound on files scanned in project mode.
+    const MAX_PROJECT_FILES: usize = 20_000;
+    /// Refuse to preview more hunks than this; the search should be narrowed.
+    const MAX_PROJECT_HUNKS: usize = 500;
+
+    /// Tool for performing exact find-and-replace operations on files.
+    pub struct FindReplaceTool {
+        tool: Tool,
+        pub(crate) workspace_root: PathBuf,
+    }
+
+    /// Matches of the search string within one file, pending selection.
+    struct FileMatches {
+        path: PathBuf,
+        file: TextFile,
+        offsets: Vec<usize>,
+        /// Where the replacement text departs from this file's style; it is
+        /// inserted verbatim, as in single-file mode.
+        style_warnings: Vec<String>,
+    }
+
+    impl FindReplaceTool {
+        /// Build the tool definition and schema metadata.
+        pub fn new() -> Self {
+            let mut parameters = HashMap::new();
+
+            // filePath parameter (required)
+            let mut file_path_items = HashMap::new();
+            file_path_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "filePath".to_string(),
+                Parameter {
+                    items: file_path_items,
+                    description: "Absolute path to the file to modify, or the directory to search when scope is 'project'.".to_string(),
+                    enum_values: None,
+                },
+            );
+
+            // searchContent parameter (required)
+            let mut search_items = HashMap::new();
+            search_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "searchContent".to_string(),
+                Parameter {
+                    items: search_items,
+                    description: "Exact text to search for within the file.".to_string(),
+                    enum_values: None,
+                },
+            );
+
+            // replaceContent parameter (required)
+            let mut replace_items = HashMap::new();
+            replace_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "replaceContent".to_string(),
+                Parameter {
+                    items: replace_items,
+                    description: "Replacement text to insert for every match.".to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let mut scope_items = HashMap::new();
+            scope_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "scope".to_string(),
+                Parameter {
+                    items: scope_items,
+                    description: "'file' (default) replaces within filePath. 'project' searches every file under the filePath directory, lets the user review the matches, and applies the accepted ones atomically.".to_string(),
+                    enum_values: Some(vec!["file".to_string(), "project".to_string()]),
+                },
+            );
+
+            let mut include_items = HashMap::new();
+            include_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "include".to_string(),
+                Parameter {
+                    items: include_items,
+                    description:
+                        "Project scope only: file name glob to limit the search, e.g. '*.rs'."
+                            .to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let tool = Tool {
+                name: "find_replace".to_string(),
+                description:
+                    "Finds and replaces all occurrences of a string within a file using exact matching. Use scope='project' to rename across a whole directory tree with user review."
+                        .to_string(),
+                parameters,
+                required: vec![
+                    "filePath".to_string(),
+                    "searchContent".to_string(),
+                    "replaceContent".to_string(),
+                ],
+            };
+
+            let workspace_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+            Self {
+                tool,
+                workspace_root,
+            }
+        }
+
+        /// Execute an exact find-and-replace against the provided file.
+        fn execute(
+            &self,
+            file_path: &str,
+            search_content: &str,
+            replace_content: &str,
+        ) -> Result<String, Box<dyn Error>> {
+            if search_content.is_empty() {
+                return Err("searchContent cannot be empty".into());
+            }
+            if search_content == replace_content {
+                return Err("searchContent and replaceContent must differ".into());
+            }
+
+            let path = Path::new(file_path);
+            if !path.exists() {
+                return Err(format!("File does not exist: {}", file_path).into());
+            }
+            if !path.is_file() {
+                return Err(format!("Path is not a file: {}", file_path).into());
+            }
+
+            let file = TextFile::read(path)?;
+            let search_content = &text_file::normalize_line_endings(search_content);
+            let replace_content = &text_file::normalize_line_endings(replace_content);
+            let style_warnings =
+                Conventions::detect(path, Some(&file.content)).check(replace_content);
+            let occurrences: Vec<_> = file
+                .content
+                .match_indices(search_content.as_str())
+                .collect();
+            if occurrences.is_empty() {
+                return Err(
+                    format!("No matches for searchContent were found in {}", file_path).into(),
+                );
+            }
+
+            let replaced = file
+                .content
+                .replace(search_content.as_str(), replace_content.as_str());
+            let mut warnings = file.write(path, &replaced)?;
+            warnings.extend(style_warnings);
+
+            Ok(text_file::annotate(
+                format!(
+                    "Replaced {} occurrence(s) of searchContent in {}",
+                    occurrences.len(),
+                    file_path
+                ),
+                &file,
+                &warnings,
+            ))
+        }
+
+        /// Search every file under `root` and apply the hunks the user accepts.
+        fn execute_project(
+            &self,
+            root: &str,
+            search_content: &str,
+            replace_content: &str,
+            include: Option<&str>,
+        ) -> Result<String, Box<dyn Error>> {
+            if search_content.is_empty() {
+                return Err("searchContent cannot be empty".into());
+            }
+            if search_content == replace_content {
+                return Err("searchContent and replaceContent must differ".into());
+            }
+            let root_path = Path::new(root);
+            if !root_path.is_dir() {
+                return Err(format!("Directory does not exist: {}", root).into());
+            }
+
+            let search = text_file::normalize_line_endings(search_content);
+            let replace = text_file::normalize_line_endings(replace_content);
+
+            let mut matches = Vec::new();
+            for path in
+                workspace_files::collect_files(root_path, MAX_PROJECT_FILES, MAX_PROJECT_FILE_BYTES)
+            {
+                if let Some(pattern) = include {
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    if !workspace_files::matches_glob(name, pattern) {
+                        continue;
+                    }
+                }
+                // Binary or undecodable files are skipped
+                let Ok(file) = TextFile::read(&path) else {
+                    continue;
+                };
+                if file.content.contains('\0') {
+                    continue;
+                }
+                let offsets: Vec<usize> = file
+                    .content
+                    .match_indices(search.as_str())
+                    .map(|(i, _)| i)
+                    .collect();
+                if !offsets.is_empty() {
+                    let style_warnings =
+                        Conventions::detect(&path, Some(&file.content)).check(&replace);
+                    matches.push(FileMatches {
+                        path,
+                        file,
+                        offsets,
+                        style_warnings,
+                    });
+                }
+            }
+
+            let total: usize = matches.iter().map(|m| m.offsets.len()).sum();
+            if total == 0 {
+                return Err(
+                    format!("No matches for searchContent were found under {}", root).into(),
+                );
+            }
+            if total > MAX_PROJECT_HUNKS {
+                return Err(format!(
+                    "Found {} matches in {} files, more than the {} that can be reviewed at once; narrow the search with include or a more specific searchContent",
+                    total,
+                    matches.len(),
+                    MAX_PROJECT_HUNKS
+                )
+                .into());
+            }
+
+            let mut items: Vec<SelectionItem> = Vec::with_capacity(total);
+            for m in &matches {
+                let group = display_path(root_path, &m.path);
+                for &offset in &m.offsets {
+                    items.push(hunk_item(
+                        &group,
+                        &m.file.content,
+                        offset,
+                        &search,
+                        &replace,
+                    ));
+                }
+            }
+            let title = format!(
+                "Replace {:?} with {:?} ({} matches in {} files)",
+                search,
+                replace,
+                total,
+                matches.len()
+            );
+            let accepted = approval::select("find_replace", &title, items).ok_or(
+                "Project-wide replace was cancelled (or no reviewer is available; set PENGY_AUTO_APPROVE=1 to apply every match); no files were changed",
+            )?;
+
+            // Build the new contents first so nothing is written unless every
+            // file can be updated
+            let mut planned = Vec::new();
+            let mut flags = accepted.into_iter();
+            for m in &matches {
+                let keep: Vec<bool> = flags.by_ref().take(m.offsets.len()).collect();
+                let count = keep.iter().filter(|k| **k).count();
+                if count == 0 {
+                    continue;
+                }
+                let content = apply_hunks(&m.file.content, &m.offsets, &keep, &search, &replace);
+                planned.push((m, content, count));
+            }
+            if planned.is_empty() {
+                return Ok("No matches were accepted; no files were changed.".to_string());
+            }
+
+            self.apply_atomically(root_path, &planned, total)
+        }
+
+        /// Snapshot every file into the trash, then write them all, restoring
+        /// the already-written ones if any write fails.
+        fn apply_atomically(
+            &self,
+            root: &Path,
+            planned: &[(&FileMatches, String, usize)],
+            total: usize,
+        ) -> Result<String, Box<dyn Error>> {
+            // The review may have taken a while; never overwrite edits made
+            // in the meantime with contents computed before it
+            for (m, _, _) in planned {
+                let current = TextFile::read(&m.path).map(|f| f.content).ok();
+                if current.as_deref() != Some(m.file.content.as_str()) {
+                    return Err(format!(
+                        "{} changed while the replacement was being reviewed; no files were changed. Run find_replace again.",
+                        m.path.display()
+                    )
+                    .into());
+                }
+            }
+
+            let mut originals = Vec::new();
+            for (m, _, _) in planned {
+                originals.push(fs::read(&m.path)?);
+                trash::snapshot(&self.workspace_root, &m.path, "find_replace")?;
+            }
+
+            let mut lines = Vec::new();
+            for (index, (m, content, count)) in planned.iter().enumerate() {
+                match m.file.write(&m.path, content) {
+                    Ok(mut warnings) => {
+                        warnings.extend(m.style_warnings.iter().cloned());
+                        lines.push(text_file::annotate(
+                            format!("- {}: {}", display_path(root, &m.path), count),
+                            &m.file,
+                            &warnings,
+                        ))
+                    }
+                    Err(e) => {
+                        for (done, original) in planned.iter().zip(&originals).take(index) {
+                            let _ = file_write::write_preserving(&done.0.path, original);
+                        }
+                        return Err(format!(
+                            "Failed to write {}: {}. All changes were rolled back.",
+                            m.path.display(),
+                            e
+                        )
+                        .into());
+                    }
+                }
+            }
+
+            let applied: usize = planned.iter().map(|(_, _, c)| c).sum();
+            Ok(format!(
+                "Replaced {} of {} match(es) across {} file(s) (originals saved to trash; restore_file can undo):\n{}",
+                applied,
+                total,
+                planned.len(),
+                lines.join("\n")
+            ))
+        }
+    }
+
+    fn display_path(root: &Path, path: &Path) -> String {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    /// Describe the match at `offset` as a one-line hunk with a preview of the
+    /// affected line before and after replacement.
+    fn hunk_item(
+        group: &str,
+        content: &str,
+        offset: usize,
+        search: &str,
+        replace: &str,
+    ) -> SelectionItem {
+        let line_start = content[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let match_end = offset + search.len();
+        let line_end = content[match_end..]
+            .find('\n')
+            .map(|i| match_end + i)
+            .unwrap_or(content.len());
+        let line_number = content[..offset].matches('\n').count() + 1;
+        let before = &content[line_start..line_end];
+        let after = format!(
+            "{}{}{}",
+            &content[line_start..offset],
+            replace,
+            &content[match_end..line_end]
+        );
+        SelectionItem {
+            group: group.to_string(),
+            label: format!("L{}: {}", line_number, before.trim()),
+            detail: format!("- {}\n+ {}", before, after),
+        }
+    }
+
+    /// Replace the matches at `offsets` whose `keep` flag is set.
+    fn apply_hunks(
+        content: &str,
+        offsets: &[usize],
+        keep: &[bool],
+        search: &str,
+        replace: &str,
+    ) -> String {
+        let mut result = String::with_capacity(content.len());
+        let mut last = 0;
+        for (&offset, &accepted) in offsets.iter().zip(keep) {
+            if !accepted {
+                continue;
+            }
+            result.push_str(&content[last..offset]);
+            result.push_str(replace);
+            last = offset + search.len();
+        }
+        result.push_str(&content[last..]);
+        result
+    }
+
+    impl ToolCall for FindReplaceTool {
+        fn get_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+            self.tool.get_json()
+        }
+
+        /// Parse arguments and run the find/replace operation.
+        fn run(&self, arguments: &str) -> Result<String, Box<dyn Error>> {
+            let args: serde_json::Value = serde_json::from_str(arguments)?;
+
+            let file_path = args
+                .get("filePath")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: filePath")?;
+
+            let search_content = args
+                .get("searchContent")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: searchContent")?;
+
+            let replace_content = args
+                .get("replaceContent")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: replaceContent")?;
+
+            match args.get("scope").and_then(|v| v.as_str()).unwrap_or("file") {
+                "project" => {
+                    let include = args.get("include").and_then(|v| v.as_str());
+                    self.execute_project(file_path, search_content, replace_content, include)
+                }
+                "file" => self.execute(file_path, search_content, replace_content),
+                other => Err(format!("Unknown scope: {} (use 'file' or 'project')", other).into()),
+            }
+        }
+
+        fn name(&self) -> &str {
+            "find_replace"
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::FindReplaceTool;
+        use crate::tool::approval::approval;
+        use crate::tool::tool::tool::ToolCall;
+        use std::fs;
+        use tempfile::NamedTempFile;
+
+        #[test]
+        fn test_find_replace_success() {
+            let mut tmp = NamedTempFile::new().unwrap();
+            fs::write(tmp.path(), "alpha beta alpha").unwrap();
+
+            let tool = FindReplaceTool::new();
+            let args = format!(
+                r#"{{
+                "filePath": "{}",
+                "searchContent": "alpha",
+                "replaceContent": "gamma"
+            }}"#,
+                tmp.path().display()
+            );
+
+            let result = tool.run(&args);
+            assert!(result.is_ok(), "Tool should run successfully: {:?}", result);
+
+            let content = fs::read_to_string(tmp.path()).unwrap();
+            assert_eq!(content, "gamma beta gamma");
+        }
+
+        #[test]
+        fn test_find_replace_no_matches() {
+            let mut tmp = NamedTempFile::new().unwrap();
+            fs::write(tmp.path(), "no targets here").unwrap();
+
+            let tool = FindReplaceTool::new();
+            let args = format!(
+                r#"{{
+                "filePath": "{}",
+                "searchContent": "missing",
+                "replaceContent": "found"
+            }}"#,
+                tmp.path().display()
+            );
+
+            let result = tool.run(&args);
+            assert!(result.is_err(), "Expected error when nothing matches");
+        }
+
+        #[test]
+        fn test_find_replace_preserves_crlf() {
+            let tmp = NamedTempFile::new().unwrap();
+            fs::write(tmp.path(), "first line\r\nsecond line\r\n").unwrap();
+
+            let tool = FindReplaceTool::new();
+            let args = format!(
+                r#"{{
+                "filePath": "{}",
+                "searchContent": "first line\nsecond",
+                "replaceContent": "line one\nline two"
+            }}"#,
+                tmp.path().display()
+            );
+
+            let result = tool.run(&args);
+            assert!(result.is_ok(), "Tool should run successfully: {:?}", result);
+
+            let content = fs::read(tmp.path()).unwrap();
+            assert_eq!(content, b"line one\r\nline two line\r\n");
+        }
+
+        #[test]
+        fn test_find_replace_inserts_text_verbatim() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let path = dir.path().join("main.go");
+            fs::write(&path, "func a() {\n\tx()\n\ty()\n\tz()\n}\n").unwrap();
+
+            let tool = FindReplaceTool::new();
+            let args = serde_json::json!({
+                "filePath": path,
+                "searchContent": "\tz()",
+                "replaceContent": "    if ok {\n        z()\n    }"
+            });
+            let result = tool.run(&args.to_string()).unwrap();
+            assert!(result.contains("indented with spaces"), "{}", result);
+            assert_eq!(
+                fs::read_to_string(&path).unwrap(),
+                "func a() {\n\tx()\n\ty()\n    if ok {\n        z()\n    }\n}\n"
+            );
+        }
+
+        #[test]
+        fn test_apply_hunks_skips_rejected_matches() {
+            let content = "foo bar foo baz foo";
+            let offsets: Vec<usize> = content.match_indices("foo").map(|(i, _)| i).collect();
+            let result = super::apply_hunks(content, &offsets, &[true, false, true], "foo", "qux");
+            assert_eq!(result, "qux bar foo baz qux");
+        }
+
+        #[test]
+        fn test_find_replace_project_scope() {
+            let dir = tempfile::TempDir::new().unwrap();
+            fs::create_dir_all(dir.path().join("src")).unwrap();
+            fs::write(
+                dir.path().join("src/a.rs"),
+                "fn old_name() {}\nold_name();\n",
+            )
+            .unwrap();
+            fs::write(dir.path().join("src/b.rs"), "use crate::old_name;\n").unwrap();
+            fs::write(dir.path().join("notes.md"), "old_name stays\n").unwrap();
+
+            let mut tool = FindReplaceTool::new();
+            tool.workspace_root = dir.path().to_path_buf();
+            let args = serde_json::json!({
+                "filePath": dir.path(),
+                "searchContent": "old_name",
+                "replaceContent": "new_name",
+                "scope": "project",
+                "include": "*.rs"
+            });
+
+            // Nobody to review the hunks: nothing is applied
+            approval::clear_selection_handler();
+            let err = tool.run(&args.to_string()).unwrap_err().to_string();
+            assert!(err.contains("cancelled"), "{}", err);
+            assert_eq!(
+                fs::read_to_string(dir.path().join("src/b.rs")).unwrap(),
+                "use crate::old_name;\n"
+            );
+
+            // A file edited while the picker is open aborts the replace
+            let edited = dir.path().join("src/b.rs");
+            approval::set_selection_handler(Box::new(move |request| {
+                fs::write(&edited, "use crate::old_name; // edited\n").unwrap();
+                Some(vec![true; request.items.len()])
+            }));
+            let err = tool.run(&args.to_string()).unwrap_err().to_string();
+            assert!(err.contains("changed while"), "{}", err);
+            assert_eq!(
+                fs::read_to_string(dir.path().join("src/a.rs")).unwrap(),
+                "fn old_name() {}\nold_name();\n"
+            );
+            fs::write(dir.path().join("src/b.rs"), "use crate::old_name;\n").unwrap();
+
+            approval::set_selection_handler(Box::new(|request| {
+                Some(vec![true; request.items.len()])
+            }));
+            let result = tool.run(&args.to_string()).unwrap();
+            approval::clear_selection_handler();
+            assert!(
+                result.contains("Replaced 3 of 3 match(es) across 2 file(s)"),
+                "{}",
+                result
+            );
+            assert_eq!(
+                fs::read_to_string(dir.path().join("src/a.rs")).unwrap(),
+                "fn new_name() {}\nnew_name();\n"
+            );
+            assert_eq!(
+                fs::read_to_string(dir.path().join("src/b.rs")).unwrap(),
+                "use crate::new_name;\n"
+            );
+            assert_eq!(
+                fs::read_to_string(dir.path().join("notes.md")).unwrap(),
+                "old_name stays\n"
+            );
+        }
+    }
+}
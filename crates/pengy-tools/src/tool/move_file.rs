@@ -0,0 +1,668 @@
+pub mod move_file {
+    //! Rename or move a file or directory inside the workspace, using `git mv`
+    //! when the source is tracked, and optionally rewrite references to the old
+    //! location.
+    //!
+    //! Reference updating is regex-assisted: quoted path literals (JS/TS
+    //! imports, `require`, C/C++ `#include "..."`, CSS `@import`, config files)
+    //! that resolve to the moved path are rewritten relative to the file that
+    //! contains them, and Python `import`/`from` statements are rewritten when a
+    //! module moves. Relative references inside moved files are fixed up too.
+    use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use crate::util::text_file::text_file::{self, TextFile};
+    use crate::util::trash::trash;
+    use crate::util::workspace_files::workspace_files;
+    use once_cell::sync::Lazy;
+    use regex::Regex;
+    use serde_json;
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::fs;
+    use std::path::{Component, Path, PathBuf};
+    use std::process::Command;
+
+    /// Files larger than this are not scanned for references.
+    const MAX_SCAN_BYTES: u64 = 1024 * 1024;
+    /// Upper bound on files visited while updating references.
+    const MAX_SCAN_FILES: usize = 20_000;
+    /// Extensions tried when an extension-less import is resolved.
+    const IMPLICIT_EXTENSIONS: &[&str] = &[
+        "ts", "tsx", "js", "jsx", "mjs", "cjs", "vue", "svelte", "py", "json", "css", "scss",
+    ];
+
+    /// Moves files and directories and keeps references to them intact.
+    pub struct MoveFileTool {
+        tool: Tool,
+        pub(crate) workspace_root: PathBuf,
+    }
+
+    static LITERAL: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#""([^"\n]+)"|'([^'\n]+)'|`([^`\n]+)`"#).unwrap());
+    static IDENTIFIER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap());
+
+    /// A completed move, used to map old locations to new ones.
+    struct Relocation {
+        workspace: PathBuf,
+        old: PathBuf,
+        new: PathBuf,
+        is_dir: bool,
+        /// Matcher for `import old.module` / `from old.module` and the new name.
+        python: Option<(Regex, String)>,
+    }
+
+    impl Relocation {
+        fn new(workspace: PathBuf, old: PathBuf, new: PathBuf, is_dir: bool) -> Self {
+            let python = match (
+                python_module(&workspace, &old, is_dir),
+                python_module(&workspace, &new, is_dir),
+            ) {
+                (Some(old_module), Some(new_module)) => {
+                    let pattern = format!(
+                        r"(?m)^(\s*(?:from|import)\s+){}\b",
+                        regex::escape(&old_module)
+                    );
+                    Regex::new(&pattern).ok().map(|re| (re, new_module))
+                }
+                _ => None,
+            };
+            Self {
+                workspace,
+                old,
+                new,
+                is_dir,
+                python,
+            }
+        }
+
+        /// Where `path` (an old location) lives after the move, if it was moved.
+        /// `allow_stem` also matches an extension-less reference to a moved file.
+        fn map(&self, path: &Path, allow_stem: bool) -> Option<PathBuf> {
+            if let Ok(rest) = path.strip_prefix(&self.old) {
+                return Some(if rest.as_os_str().is_empty() {
+                    self.new.clone()
+                } else {
+                    self.new.join(rest)
+                });
+            }
+            if allow_stem && !self.is_dir && path == self.old.with_extension("") {
+                return Some(self.new.with_extension(""));
+            }
+            None
+        }
+
+        /// Where a file now at `path` used to live.
+        fn previous_location(&self, path: &Path) -> PathBuf {
+            match path.strip_prefix(&self.new) {
+                Ok(rest) if rest.as_os_str().is_empty() => self.old.clone(),
+                Ok(rest) => self.old.join(rest),
+                Err(_) => path.to_path_buf(),
+            }
+        }
+
+        /// Rewrite references in `content`, which belongs to the file now at
+        /// `file`. Returns the new content and the number of rewrites.
+        fn rewrite(&self, file: &Path, content: &str) -> (String, usize) {
+            let old_file = self.previous_location(file);
+            let file_moved = old_file != file;
+            let old_dir = old_file.parent().unwrap_or(&self.workspace).to_path_buf();
+            let new_dir = file.parent().unwrap_or(&self.workspace).to_path_buf();
+
+            let mut count = 0;
+            let rewritten = LITERAL.replace_all(content, |caps: &regex::Captures| {
+                let whole = caps.get(0).unwrap().as_str();
+                let quote = &whole[..1];
+                let inner = &whole[1..whole.len() - 1];
+                match self.rewrite_literal(inner, &old_dir, &new_dir, file_moved) {
+                    Some(updated) => {
+                        count += 1;
+                        format!("{}{}{}", quote, updated, quote)
+                    }
+                    None => whole.to_string(),
+                }
+            });
+
+            let mut result = rewritten.into_owned();
+            if let Some((re, new_module)) = &self.python {
+                let python_count = re.find_iter(&result).count();
+                if python_count > 0 {
+                    let replacement = format!("${{1}}{}", new_module);
+                    result = re.replace_all(&result, replacement.as_str()).into_owned();
+                    count += python_count;
+                }
+            }
+            (result, count)
+        }
+
+        fn rewrite_literal(
+            &self,
+            literal: &str,
+            old_dir: &Path,
+            new_dir: &Path,
+            file_moved: bool,
+        ) -> Option<String> {
+            if literal.contains("://")
+                || literal.starts_with('/')
+                || literal.contains(char::is_whitespace)
+                || !(literal.contains('/') || literal.contains('.'))
+            {
+                return None;
+            }
+            // "." and ".." name the current or parent directory in nearly
+            // every language (split("."), os.listdir('.')), not a reference
+            if Path::new(literal)
+                .components()
+                .all(|c| matches!(c, Component::CurDir | Component::ParentDir))
+            {
+                return None;
+            }
+
+            let relative_style = literal.starts_with("./") || literal.starts_with("../");
+            let has_extension = Path::new(literal).extension().is_some();
+
+            // Relative to the containing file first, then to the workspace root
+            let mut bases = vec![(old_dir, new_dir)];
+            if !relative_style {
+                bases.push((self.workspace.as_path(), self.workspace.as_path()));
+            }
+
+            for (old_base, new_base) in bases {
+                let target = clean(&old_base.join(literal));
+                // A literal naming the directory it is resolved against is
+                // never a path reference
+                if target == clean(old_base) || target == self.workspace {
+                    continue;
+                }
+                if let Some(moved) = self.map(&target, !has_extension) {
+                    let updated = format_reference(new_base, &moved, relative_style);
+                    return (!updated.is_empty()).then_some(updated);
+                }
+            }
+
+            // A moved file's own relative imports must follow it
+            if file_moved && relative_style {
+                let target = clean(&old_dir.join(literal));
+                if target_exists(&target) {
+                    let updated = format_reference(new_dir, &target, true);
+                    if !updated.is_empty() && updated != literal {
+                        return Some(updated);
+                    }
+                }
+            }
+            None
+        }
+    }
+
+    /// Lexically normalize `.` and `..` components without touching the disk.
+    fn clean(path: &Path) -> PathBuf {
+        let mut cleaned = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    cleaned.pop();
+                }
+                other => cleaned.push(other.as_os_str()),
+            }
+        }
+        cleaned
+    }
+
+    /// Path of `to` relative to the directory `from`.
+    fn relative_path(from: &Path, to: &Path) -> PathBuf {
+        let from: Vec<Component> = from.components().collect();
+        let to: Vec<Component> = to.components().collect();
+        let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+        let mut result = PathBuf::new();
+        for _ in common..from.len() {
+            result.push("..");
+        }
+        for component in &to[common..] {
+            result.push(component.as_os_str());
+        }
+        result
+    }
+
+    fn format_reference(base: &Path, target: &Path, relative_style: bool) -> String {
+        let rel = relative_path(base, target)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if relative_style && !rel.starts_with("../") {
+            format!("./{}", rel)
+        } else {
+            rel
+        }
+    }
+
+    fn target_exists(path: &Path) -> bool {
+        path.exists()
+            || IMPLICIT_EXTENSIONS
+                .iter()
+                .any(|ext| path.with_extension(ext).exists())
+    }
+
+    /// Dotted Python module name for a workspace path, if it is a package
+    /// directory or a `.py` file whose components are valid identifiers.
+    fn python_module(workspace: &Path, path: &Path, is_dir: bool) -> Option<String> {
+        if !is_dir && path.extension().and_then(|e| e.to_str()) != Some("py") {
+            return None;
+        }
+        let rel = path.strip_prefix(workspace).ok()?;
+        let rel = if is_dir {
+            rel.to_path_buf()
+        } else {
+            rel.with_extension("")
+        };
+        let parts: Vec<String> = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        if parts.is_empty() || !parts.iter().all(|p| IDENTIFIER.is_match(p)) {
+            return None;
+        }
+        Some(parts.join("."))
+    }
+
+    impl MoveFileTool {
+        pub fn new() -> Self {
+            let mut parameters = HashMap::new();
+
+            let mut source_items = HashMap::new();
+            source_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "source_path".to_string(),
+                Parameter {
+                    items: source_items,
+                    description: "File or directory to move. Must be inside the workspace."
+                        .to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let mut destination_items = HashMap::new();
+            destination_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "destination_path".to_string(),
+                Parameter {
+                    items: destination_items,
+                    description: "New path for the file or directory. Parent directories are created as needed."
+                        .to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let mut update_items = HashMap::new();
+            update_items.insert("type".to_string(), "boolean".to_string());
+            parameters.insert(
+                "update_references".to_string(),
+                Parameter {
+                    items: update_items,
+                    description: "If true, rewrite import/include paths and Python module imports across the workspace that point at the old location (default false)."
+                        .to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let mut overwrite_items = HashMap::new();
+            overwrite_items.insert("type".to_string(), "boolean".to_string());
+            parameters.insert(
+                "overwrite".to_string(),
+                Parameter {
+                    items: overwrite_items,
+                    description: "If true, an existing destination is moved to the session trash first (default false)."
+                        .to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let tool = Tool {
+                name: "move_file".to_string(),
+                description: "Rename or move a file or directory inside the workspace (uses git mv for tracked files). Set update_references=true to also fix imports/includes that point at the old path. Prefer this over reading, rewriting and deleting files."
+                    .to_string(),
+                parameters,
+                required: vec!["source_path".to_string(), "destination_path".to_string()],
+            };
+
+            let workspace_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+            Self {
+                tool,
+                workspace_root,
+            }
+        }
+
+        fn workspace(&self) -> PathBuf {
+            self.workspace_root
+                .canonicalize()
+                .unwrap_or_else(|_| self.workspace_root.clone())
+        }
+
+        fn resolve_path(&self, workspace: &Path, raw: &str) -> Result<PathBuf, Box<dyn Error>> {
+            let candidate = PathBuf::from(raw);
+            let candidate = if candidate.is_absolute() {
+                candidate
+            } else {
+                workspace.join(candidate)
+            };
+            let candidate = candidate
+                .canonicalize()
+                .unwrap_or_else(|_| clean(&candidate));
+            if !candidate.starts_with(workspace) || candidate == workspace {
+                return Err(format!(
+                    "Refusing to move outside workspace. Path: {}, workspace: {}",
+                    candidate.display(),
+                    workspace.display()
+                )
+                .into());
+            }
+            Ok(candidate)
+        }
+
+        fn is_tracked(workspace: &Path, path: &Path) -> bool {
+            Command::new("git")
+                .arg("-C")
+                .arg(workspace)
+                .args(["ls-files", "--error-unmatch", "--"])
+                .arg(path)
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+        }
+
+        fn git_mv(workspace: &Path, source: &Path, destination: &Path) -> bool {
+            Command::new("git")
+                .arg("-C")
+                .arg(workspace)
+                .arg("mv")
+                .arg(source)
+                .arg(destination)
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+        }
+
+        /// Rewrite references across the workspace, returning a line per
+        /// updated file.
+        fn update_references(&self, relocation: &Relocation) -> Vec<String> {
+            let mut updated = Vec::new();
+            for path in workspace_files::collect_files(
+                &relocation.workspace,
+                MAX_SCAN_FILES,
+                MAX_SCAN_BYTES,
+            ) {
+                // Binary or undecodable files are skipped
+                let Ok(file) = TextFile::read(&path) else {
+                    continue;
+                };
+                if file.content.contains('\0') {
+                    continue;
+                }
+                let (content, count) = relocation.rewrite(&path, &file.content);
+                if count == 0 || content == file.content {
+                    continue;
+                }
+                let display = path
+                    .strip_prefix(&relocation.workspace)
+                    .unwrap_or(&path)
+                    .display()
+                    .to_string();
+                match file.write(&path, &content) {
+                    Ok(warnings) => updated.push(text_file::annotate(
+                        format!("- {} ({} reference(s))", display, count),
+                        &file,
+                        &warnings,
+                    )),
+                    Err(e) => updated.push(format!("- {} (not updated: {})", display, e)),
+                }
+            }
+            updated
+        }
+    }
+
+    impl ToolCall for MoveFileTool {
+        fn get_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+            self.tool.get_json()
+        }
+
+        fn run(&self, arguments: &str) -> Result<String, Box<dyn Error>> {
+            let args: serde_json::Value = serde_json::from_str(arguments)?;
+            let source = args
+                .get("source_path")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: source_path")?;
+            let destination = args
+                .get("destination_path")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: destination_path")?;
+            let update_references = args
+                .get("update_references")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let overwrite = args
+                .get("overwrite")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+
+            let workspace = self.workspace();
+            let source = self.resolve_path(&workspace, source)?;
+            if !source.exists() {
+                return Err(format!("Path does not exist: {}", source.display()).into());
+            }
+            let mut destination = self.resolve_path(&workspace, destination)?;
+            // Moving into an existing directory keeps the source's name
+            if destination.is_dir()
+                && !source.is_dir()
+                && let Some(name) = source.file_name()
+            {
+                destination = destination.join(name);
+            }
+            if destination == source {
+                return Err("Source and destination are the same path".into());
+            }
+            if destination.starts_with(&source) {
+                return Err("Cannot move a directory inside itself".into());
+            }
+
+            let mut notes = Vec::new();
+            if destination.exists() {
+                if !overwrite {
+                    return Err(format!(
+                        "Destination already exists: {} (set overwrite=true to replace it)",
+                        destination.display()
+                    )
+                    .into());
+                }
+                notes.push(
+                    match trash::move_to_trash(&workspace, &destination, "replaced by move")? {
+                        Some(entry) => {
+                            format!("Previous destination moved to trash as {}", entry.id)
+                        }
+                        None => "Previous destination was too large for the trash and was deleted permanently"
+                            .to_string(),
+                    },
+                );
+            }
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let is_dir = source.is_dir();
+            let method = if Self::is_tracked(&workspace, &source)
+                && Self::git_mv(&workspace, &source, &destination)
+            {
+                "git mv"
+            } else {
+                fs::rename(&source, &destination)?;
+                "rename"
+            };
+
+            let mut output = format!(
+                "Moved {} -> {} ({})",
+                source.display(),
+                destination.display(),
+                method
+            );
+            for note in notes {
+                output.push_str(&format!("\n{}", note));
+            }
+
+            if update_references {
+                let relocation = Relocation::new(workspace, source, destination, is_dir);
+                let updated = self.update_references(&relocation);
+                if updated.is_empty() {
+                    output.push_str("\nNo references to the old path were found.");
+                } else {
+                    output.push_str(&format!(
+                        "\nUpdated references in {} file(s):\n{}",
+                        updated.len(),
+                        updated.join("\n")
+                    ));
+                }
+            }
+
+            Ok(output)
+        }
+
+        fn name(&self) -> &str {
+            "move_file"
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use tempfile::TempDir;
+
+        fn create_test_tool() -> (MoveFileTool, TempDir) {
+            let temp_dir = TempDir::new().unwrap();
+            let mut tool = MoveFileTool::new();
+            tool.workspace_root = temp_dir.path().to_path_buf();
+            (tool, temp_dir)
+        }
+
+        #[test]
+        fn test_relative_path() {
+            assert_eq!(
+                relative_path(Path::new("/w/src/a"), Path::new("/w/lib/b.ts")),
+                PathBuf::from("../../lib/b.ts")
+            );
+            assert_eq!(
+                format_reference(Path::new("/w/src"), Path::new("/w/src/util/x"), true),
+                "./util/x"
+            );
+        }
+
+        #[test]
+        fn test_move_updates_imports() {
+            let (tool, temp_dir) = create_test_tool();
+            let root = temp_dir.path();
+            fs::create_dir_all(root.join("src/utils")).unwrap();
+            fs::write(
+                root.join("src/utils/format.ts"),
+                "import { x } from '../config';\nexport const f = 1;\n",
+            )
+            .unwrap();
+            fs::write(root.join("src/config.ts"), "export const x = 1;\n").unwrap();
+            fs::write(
+                root.join("src/app.ts"),
+                "import { f } from \"./utils/format\";\nconst s = \"plain text\";\n",
+            )
+            .unwrap();
+
+            let result = tool
+                .run(
+                    r#"{"source_path": "src/utils/format.ts", "destination_path": "src/lib/text/format.ts", "update_references": true}"#,
+                )
+                .unwrap();
+            assert!(
+                result.contains("Updated references in 2 file(s)"),
+                "{}",
+                result
+            );
+
+            assert!(!root.join("src/utils/format.ts").exists());
+            assert_eq!(
+                fs::read_to_string(root.join("src/app.ts")).unwrap(),
+                "import { f } from \"./lib/text/format\";\nconst s = \"plain text\";\n"
+            );
+            assert_eq!(
+                fs::read_to_string(root.join("src/lib/text/format.ts")).unwrap(),
+                "import { x } from '../../config';\nexport const f = 1;\n"
+            );
+        }
+
+        #[test]
+        fn test_move_keeps_dot_literals() {
+            let (tool, temp_dir) = create_test_tool();
+            let root = temp_dir.path();
+            fs::create_dir_all(root.join("src/utils")).unwrap();
+            let source = "const parts = name.split(\".\");\nconst up = path.join(dir, '..');\n";
+            fs::write(root.join("src/utils/name.ts"), source).unwrap();
+            fs::write(
+                root.join("src/utils/list.py"),
+                "os.listdir('.')\nos.listdir('./')\n",
+            )
+            .unwrap();
+
+            tool.run(
+                r#"{"source_path": "src/utils", "destination_path": "src/helpers", "update_references": true}"#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                fs::read_to_string(root.join("src/helpers/name.ts")).unwrap(),
+                source
+            );
+            assert_eq!(
+                fs::read_to_string(root.join("src/helpers/list.py")).unwrap(),
+                "os.listdir('.')\nos.listdir('./')\n"
+            );
+        }
+
+        #[test]
+        fn test_move_updates_python_and_includes() {
+            let (tool, temp_dir) = create_test_tool();
+            let root = temp_dir.path();
+            fs::create_dir_all(root.join("pkg")).unwrap();
+            fs::write(root.join("pkg/helpers.py"), "def go():\n    pass\n").unwrap();
+            fs::write(
+                root.join("main.py"),
+                "from pkg.helpers import go\nimport pkg.helpers as h\n",
+            )
+            .unwrap();
+            fs::write(root.join("pkg/util.h"), "#pragma once\n").unwrap();
+            fs::write(root.join("main.c"), "#include \"pkg/util.h\"\n").unwrap();
+
+            tool.run(
+                r#"{"source_path": "pkg/helpers.py", "destination_path": "pkg/tools.py", "update_references": true}"#,
+            )
+            .unwrap();
+            tool.run(
+                r#"{"source_path": "pkg/util.h", "destination_path": "include/util.h", "update_references": true}"#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                fs::read_to_string(root.join("main.py")).unwrap(),
+                "from pkg.tools import go\nimport pkg.tools as h\n"
+            );
+            assert_eq!(
+                fs::read_to_string(root.join("main.c")).unwrap(),
+                "#include \"include/util.h\"\n"
+            );
+        }
+
+        #[test]
+        fn test_move_refuses_existing_destination() {
+            let (tool, temp_dir) = create_test_tool();
+            fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+            fs::write(temp_dir.path().join("b.txt"), "b").unwrap();
+
+            let result = tool.run(r#"{"source_path": "a.txt", "destination_path": "b.txt"}"#);
+            assert!(result.is_err());
+            assert_eq!(
+                fs::read_to_string(temp_dir.path().join("b.txt")).unwrap(),
+                "b"
+            );
+        }
+    }
+}
@@ -1,18 +1,24 @@
 pub mod read_file {
     //! Read file contents with optional line slicing. This is intended for quick
-    //! inspection of files without modifying them.
+    //! inspection of files without modifying them. The first read of each file
+    //! also reports its detected formatting conventions so edits can follow
+    //! them.
     use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use crate::util::conventions::conventions::Conventions;
     use serde_json;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
     use std::error::Error;
     use std::fs;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
 
     const DEFAULT_MAX_LINES: usize = 250;
 
     /// Simple file reader tool.
     pub struct ReadFileTool {
         tool: Tool,
+        /// Files whose conventions were already reported to this agent.
+        described: Mutex<HashSet<PathBuf>>,
     }
 
     impl ReadFileTool {
@@ -72,18 +78,16 @@ pub mod read_file {
                 required: vec!["target_file".to_string()],
             };
 
-            Self { tool }
-        }
-
-        fn read_entire(path: &Path) -> Result<String, Box<dyn Error>> {
-            Ok(fs::read_to_string(path)?)
+            Self {
+                tool,
+                described: Mutex::new(HashSet::new()),
+            }
         }
 
-        fn read_slice(path: &Path, start: usize, end: usize) -> Result<String, Box<dyn Error>> {
-            let content = fs::read_to_string(path)?;
+        fn read_slice(content: &str, start: usize, end: usize) -> String {
             let lines: Vec<&str> = content.lines().collect();
             if lines.is_empty() {
-                return Ok("File is empty.".to_string());
+                return "File is empty.".to_string();
             }
             let start_idx = start.saturating_sub(1);
             let end_idx = end.min(lines.len());
@@ -93,7 +97,25 @@ pub mod read_file {
                 let line_no = start_idx + offset + 1;
                 output.push(format!("L{}:{}", line_no, line));
             }
-            Ok(output.join("\n"))
+            output.join("\n")
+        }
+
+        /// Append the file's indentation, quote and line length conventions
+        /// the first time the file is read.
+        fn with_conventions(&self, path: &Path, content: &str, output: String) -> String {
+            let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            let first_read = self
+                .described
+                .lock()
+                .map(|mut described| described.insert(key))
+                .unwrap_or(false);
+            if !first_read {
+                return output;
+            }
+            match Conventions::detect(path, Some(content)).describe() {
+                Some(summary) => format!("{}\n\n[File conventions: {}]", output, summary),
+                None => output,
+            }
         }
     }
 
@@ -131,8 +153,9 @@ pub mod read_file {
                 .and_then(|v| v.as_u64())
                 .map(|v| v as usize);
 
+            let content = fs::read_to_string(path)?;
             if read_entire || (start.is_none() && end.is_none()) {
-                return Self::read_entire(path);
+                return Ok(self.with_conventions(path, &content, content.clone()));
             }
 
             let start = start.unwrap_or(1);
@@ -143,11 +166,35 @@ pub mod read_file {
             }
             let end = (end - start + 1).min(DEFAULT_MAX_LINES) + start - 1;
 
-            Self::read_slice(path, start, end)
+            let slice = Self::read_slice(&content, start, end);
+            Ok(self.with_conventions(path, &content, slice))
         }
 
         fn name(&self) -> &str {
             "read_file"
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_conventions_reported_once_per_file() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let path = dir.path().join("main.py");
+            fs::write(&path, "def a():\n    if x:\n        y()\n    z()\n").unwrap();
+            let tool = ReadFileTool::new();
+            let args = serde_json::json!({ "target_file": path }).to_string();
+
+            let first = tool.run(&args).unwrap();
+            assert!(
+                first.contains("[File conventions: indent: 4 spaces"),
+                "{}",
+                first
+            );
+            let second = tool.run(&args).unwrap();
+            assert!(!second.contains("[File conventions"), "{}", second);
+        }
+    }
 }
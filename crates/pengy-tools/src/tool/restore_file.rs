@@ -0,0 +1,148 @@
+pub mod restore_file {
+    //! Restore files and directories from the session trash populated by
+    //! `delete_file` and file_manager overwrites.
+    use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use crate::util::trash::trash::{self, TrashEntry};
+    use serde_json;
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::path::PathBuf;
+
+    /// Lists trash entries or puts one back at its original location.
+    pub struct RestoreFileTool {
+        tool: Tool,
+        pub(crate) workspace_root: PathBuf,
+    }
+
+    impl RestoreFileTool {
+        pub fn new() -> Self {
+            let mut parameters = HashMap::new();
+
+            let mut path_items = HashMap::new();
+            path_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "target_file".to_string(),
+                Parameter {
+                    items: path_items,
+                    description: "Original path to restore. The most recent trash entry for this path is used."
+                        .to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let mut id_items = HashMap::new();
+            id_items.insert("type".to_string(), "string".to_string());
+            parameters.insert(
+                "entry_id".to_string(),
+                Parameter {
+                    items: id_items,
+                    description: "Id of a specific trash entry to restore (as reported by delete_file, file_manager or list)."
+                        .to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let mut list_items = HashMap::new();
+            list_items.insert("type".to_string(), "boolean".to_string());
+            parameters.insert(
+                "list".to_string(),
+                Parameter {
+                    items: list_items,
+                    description: "If true, list the trash entries of the current session instead of restoring."
+                        .to_string(),
+                    enum_values: None,
+                },
+            );
+
+            let tool = Tool {
+                name: "restore_file".to_string(),
+                description: "Undo a delete_file or a file_manager overwrite by restoring the original from the session trash. Provide entry_id or target_file, or list=true to see what can be restored. Whatever currently exists at the path is moved to the trash first."
+                    .to_string(),
+                parameters,
+                required: vec![],
+            };
+
+            let workspace_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+            Self {
+                tool,
+                workspace_root,
+            }
+        }
+
+        fn resolve_path(&self, raw: &str) -> PathBuf {
+            let workspace = self
+                .workspace_root
+                .canonicalize()
+                .unwrap_or_else(|_| self.workspace_root.clone());
+            let candidate = PathBuf::from(raw);
+            let candidate = if candidate.is_absolute() {
+                candidate
+            } else {
+                workspace.join(candidate)
+            };
+            // The path usually no longer exists, so canonicalize only its parent
+            match (candidate.parent(), candidate.file_name()) {
+                (Some(parent), Some(name)) => parent
+                    .canonicalize()
+                    .map(|p| p.join(name))
+                    .unwrap_or(candidate.clone()),
+                _ => candidate,
+            }
+        }
+
+        fn format_list(entries: &[TrashEntry]) -> String {
+            entries
+                .iter()
+                .map(|e| format!("- {}", e.summary()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    impl ToolCall for RestoreFileTool {
+        fn get_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+            self.tool.get_json()
+        }
+
+        fn run(&self, arguments: &str) -> Result<String, Box<dyn Error>> {
+            let args: serde_json::Value = serde_json::from_str(arguments)?;
+            let entry_id = args.get("entry_id").and_then(|v| v.as_str());
+            let target = args.get("target_file").and_then(|v| v.as_str());
+            let list = args.get("list").and_then(|v| v.as_bool()).unwrap_or(false);
+
+            if list || (entry_id.is_none() && target.is_none()) {
+                let session = trash::current_session();
+                let entries = trash::list(&self.workspace_root, Some(&session));
+                if entries.is_empty() {
+                    return Ok("Trash is empty for this session.".to_string());
+                }
+                return Ok(format!(
+                    "Trash entries (newest first):\n{}",
+                    Self::format_list(&entries)
+                ));
+            }
+
+            let entry = if let Some(id) = entry_id {
+                trash::find(&self.workspace_root, id)
+                    .ok_or_else(|| format!("No trash entry with id {}", id))?
+            } else {
+                let path = self.resolve_path(target.unwrap_or_default());
+                let session = trash::current_session();
+                let mut entries = trash::list(&self.workspace_root, None);
+                // Prefer the current session's entries, newest first
+                entries.sort_by_key(|e| e.session != session);
+                entries
+                    .into_iter()
+                    .find(|e| e.original_path == path)
+                    .ok_or_else(|| format!("No trash entry for {}", path.display()))?
+            };
+
+            trash::restore(&self.workspace_root, &entry)
+        }
+
+        fn name(&self) -> &str {
+            "restore_file"
+        }
+    }
+}
@@ -2,8 +2,8 @@ pub mod vector_search {
     //! Perform semantic vector search across provided text files by chunking,
     //! embedding, and scoring content against a query.
 
-    use crate::model::model::model::Model;
     use crate::tool::tool::tool::{Parameter, Tool, ToolCall};
+    use pengy_core::model::model::model::Model;
     use serde_json;
     use std::collections::HashMap;
     use std::error::Error;
@@ -160,7 +160,9 @@ pub mod vector_search {
                     .map_err(|e| e as Box<dyn Error>),
                 Err(_) => {
                     // Not in a tokio runtime, create a new one
-                    let rt = tokio::runtime::Runtime::new()?;
+                    let rt = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()?;
                     rt.block_on(model.completion_open_router_embedding(text.to_string()))
                         .map_err(|e| e as Box<dyn Error>)
                 }
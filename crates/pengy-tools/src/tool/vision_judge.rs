@@ -81,6 +81,7 @@ pub mod vision_judge {
         }
 
         /// Capture the primary screen and return a PNG data URL.
+        #[cfg(feature = "screenshot")]
         fn capture_screenshot() -> Result<String, Box<dyn Error>> {
             // Use screenshots crate to capture screen
             let screens = screenshots::Screen::all()?;
@@ -104,6 +105,11 @@ pub mod vision_judge {
             Ok(format!("data:image/png;base64,{}", base64_data))
         }
 
+        #[cfg(not(feature = "screenshot"))]
+        fn capture_screenshot() -> Result<String, Box<dyn Error>> {
+            Err("Screen capture is unavailable: built without the screenshot feature. Pass image_path instead.".into())
+        }
+
         /// Decide whether to read a provided image or capture a screenshot and
         /// return the resulting data URL.
         fn execute_vision_judge(
@@ -221,7 +221,9 @@ pub mod web {
 
             // Since reqwest is async, we need to use a runtime
             // Create a new runtime for this call
-            let rt = tokio::runtime::Runtime::new()
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
                 .map_err(|e| format!("Failed to create async runtime: {}", e))?;
 
             // Execute the async fetch
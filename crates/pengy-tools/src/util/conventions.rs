@@ -0,0 +1,756 @@
+pub mod conventions {
+    //! Detect the formatting conventions of a file (indentation, quote style,
+    //! maximum line length) so edits can follow them instead of introducing
+    //! pure style churn.
+    //!
+    //! EditorConfig settings win; otherwise line length comes from the
+    //! project's formatter configuration (rustfmt, prettier, black/ruff/flake8)
+    //! and indentation and quotes are inferred from the file itself.
+
+    use crate::util::editorconfig::editorconfig::{self, EditorConfig, IndentStyle};
+    use regex::Regex;
+    use std::fs;
+    use std::path::Path;
+
+    /// Minimum number of indented lines before indentation is inferred.
+    const MIN_INDENTED_LINES: usize = 3;
+    /// Minimum number of string literals before a quote style is inferred.
+    const MIN_QUOTED_STRINGS: usize = 4;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Indent {
+        Tabs,
+        Spaces(usize),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Quote {
+        Single,
+        Double,
+    }
+
+    /// A detected setting and where it came from, e.g. ".editorconfig",
+    /// "rustfmt.toml" or "inferred".
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Detected<T> {
+        pub value: T,
+        pub source: String,
+    }
+
+    /// Conventions that apply to one file. Anything that could not be
+    /// determined is `None`.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct Conventions {
+        pub indent: Option<Detected<Indent>>,
+        pub quote: Option<Detected<Quote>>,
+        pub max_line_length: Option<Detected<usize>>,
+        /// Columns per tab, used when converting indentation.
+        pub tab_width: Option<usize>,
+    }
+
+    impl Conventions {
+        /// Detect the conventions for `path`. `content` is the file's current
+        /// text when it exists; new files rely on configuration only.
+        pub fn detect(path: &Path, content: Option<&str>) -> Self {
+            Self::from_editorconfig(path, &editorconfig::resolve(path), content)
+        }
+
+        /// Same as `detect` but with an already resolved EditorConfig.
+        pub fn from_editorconfig(
+            path: &Path,
+            config: &EditorConfig,
+            content: Option<&str>,
+        ) -> Self {
+            let indent = match config.indent_style {
+                // Make recipes must start with a tab whatever the rest uses
+                _ if is_makefile(path) => None,
+                Some(IndentStyle::Tab) => Some(detected(".editorconfig", Indent::Tabs)),
+                Some(IndentStyle::Space) => {
+                    let width = config
+                        .indent_width()
+                        .or_else(|| content.and_then(infer_indent).and_then(space_width))
+                        .unwrap_or(4);
+                    Some(detected(".editorconfig", Indent::Spaces(width)))
+                }
+                None => content
+                    .and_then(infer_indent)
+                    .map(|v| detected("inferred", v)),
+            };
+
+            let quote = match config.quote_type.as_deref() {
+                Some("single") => Some(detected(".editorconfig", Quote::Single)),
+                Some("double") => Some(detected(".editorconfig", Quote::Double)),
+                _ if has_flexible_quotes(path) => content
+                    .and_then(infer_quote)
+                    .map(|v| detected("inferred", v)),
+                _ => None,
+            };
+
+            let max_line_length = match config.max_line_length {
+                Some(limit) => Some(detected(".editorconfig", limit)),
+                None => project_line_length(path).map(|(value, source)| Detected { value, source }),
+            };
+
+            let tab_width = config.tab_width.or(config.indent_size).or(match indent {
+                Some(Detected {
+                    value: Indent::Spaces(n),
+                    ..
+                }) => Some(n),
+                _ => None,
+            });
+
+            Self {
+                indent,
+                quote,
+                max_line_length,
+                tab_width,
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.indent.is_none() && self.quote.is_none() && self.max_line_length.is_none()
+        }
+
+        /// One-line description for tool output, e.g.
+        /// "indent: 4 spaces (inferred); max line length: 100 (rustfmt.toml)".
+        pub fn describe(&self) -> Option<String> {
+            let mut parts = Vec::new();
+            if let Some(indent) = &self.indent {
+                let value = match indent.value {
+                    Indent::Tabs => "tabs".to_string(),
+                    Indent::Spaces(n) => format!("{} spaces", n),
+                };
+                parts.push(format!("indent: {} ({})", value, indent.source));
+            }
+            if let Some(quote) = &self.quote {
+                let value = match quote.value {
+                    Quote::Single => "single",
+                    Quote::Double => "double",
+                };
+                parts.push(format!("quotes: {} ({})", value, quote.source));
+            }
+            if let Some(limit) = &self.max_line_length {
+                parts.push(format!(
+                    "max line length: {} ({})",
+                    limit.value, limit.source
+                ));
+            }
+            (!parts.is_empty()).then(|| parts.join("; "))
+        }
+
+        /// Adjust text about to be inserted into the file so its indentation
+        /// uses the file's style, and report inserted lines that break the
+        /// line length limit. Returns the adjusted text and warnings.
+        ///
+        /// Only text indented consistently with the other unit (tabs instead
+        /// of spaces or the reverse) is converted, and lines inside multi-line
+        /// strings or heredocs keep their whitespace. Text mixing both units is
+        /// left as written and the mismatch is reported instead.
+        pub fn conform(&self, inserted: &str) -> (String, Vec<String>) {
+            let mut warnings = Vec::new();
+            let mut text = inserted.to_string();
+
+            if let Some(indent) = &self.indent {
+                let style = indent_style(indent.value);
+                let (same, other) = indent_mismatch(&text, indent.value);
+                if other > 0 && same > 0 {
+                    warnings.push(format!(
+                        "the new text mixes tabs and spaces for indentation; the file uses {}",
+                        style
+                    ));
+                } else if other > 0 {
+                    let converted = convert_indentation(&text, indent.value, self.tab_width);
+                    if converted != text {
+                        warnings.push(format!(
+                            "indentation of the new text was converted to {} to match the file",
+                            style
+                        ));
+                        text = converted;
+                    }
+                }
+            }
+
+            warnings.extend(self.line_length_warning(&text));
+            (text, warnings)
+        }
+
+        /// Report where text about to be inserted departs from the file's
+        /// style without changing it, for tools that must write exactly the
+        /// text they were given.
+        pub fn check(&self, inserted: &str) -> Vec<String> {
+            let mut warnings = Vec::new();
+            if let Some(indent) = &self.indent {
+                let style = indent_style(indent.value);
+                let (same, other) = indent_mismatch(inserted, indent.value);
+                if other > 0 && same > 0 {
+                    warnings.push(format!(
+                        "the new text mixes tabs and spaces for indentation; the file uses {}",
+                        style
+                    ));
+                } else if other > 0 {
+                    let used = match indent.value {
+                        Indent::Tabs => "spaces",
+                        Indent::Spaces(_) => "tabs",
+                    };
+                    warnings.push(format!(
+                        "the new text is indented with {}; the file uses {}",
+                        used, style
+                    ));
+                }
+            }
+            warnings.extend(self.line_length_warning(inserted));
+            warnings
+        }
+
+        fn line_length_warning(&self, text: &str) -> Option<String> {
+            let limit = self.max_line_length.as_ref()?;
+            let tab_width = self.tab_width.unwrap_or(4);
+            let long = text
+                .lines()
+                .filter(|line| display_width(line, tab_width) > limit.value)
+                .count();
+            (long > 0).then(|| {
+                format!(
+                    "{} new line(s) exceed the max line length of {} ({})",
+                    long, limit.value, limit.source
+                )
+            })
+        }
+    }
+
+    fn indent_style(indent: Indent) -> String {
+        match indent {
+            Indent::Tabs => "tabs".to_string(),
+            Indent::Spaces(n) => format!("{} spaces", n),
+        }
+    }
+
+    /// Indented code lines in `text` that use the same unit as `indent`, and
+    /// those that use the other one.
+    fn indent_mismatch(text: &str, indent: Indent) -> (usize, usize) {
+        let (tab_lines, space_lines) = indented_lines(text);
+        match indent {
+            Indent::Tabs => (tab_lines, space_lines),
+            Indent::Spaces(_) => (space_lines, tab_lines),
+        }
+    }
+
+    /// Number of code lines (outside multi-line strings) indented with a tab
+    /// and with a space.
+    fn indented_lines(text: &str) -> (usize, usize) {
+        let (mut tabs, mut spaces) = (0, 0);
+        for (line, quoted) in text.split('\n').zip(string_lines(text)) {
+            if quoted || line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with('\t') {
+                tabs += 1;
+            } else if line.starts_with(' ') {
+                spaces += 1;
+            }
+        }
+        (tabs, spaces)
+    }
+
+    /// For each line of `text` (split on `\n`), whether it starts inside a
+    /// string literal: triple-quoted and raw strings, template literals,
+    /// double-quoted strings continued onto the next line, and heredoc
+    /// bodies. This is a lexical approximation; a lone `'` is ignored since
+    /// it also marks lifetimes, chars and apostrophes.
+    fn string_lines(text: &str) -> Vec<bool> {
+        #[derive(Clone, PartialEq)]
+        enum State {
+            Code,
+            BlockComment,
+            Quoted(char),
+            Triple(char),
+            Raw(usize),
+            Heredoc(String),
+        }
+
+        let mut state = State::Code;
+        let mut result = Vec::new();
+        for line in text.split('\n') {
+            result.push(!matches!(state, State::Code | State::BlockComment));
+            if let State::Heredoc(delimiter) = &state {
+                if line.trim() == delimiter {
+                    state = State::Code;
+                }
+                continue;
+            }
+
+            let chars: Vec<char> = line.chars().collect();
+            let mut heredoc = None;
+            let mut i = 0;
+            while i < chars.len() {
+                let rest = &chars[i..];
+                let starts = |s: &str| {
+                    rest.len() >= s.len() && rest.iter().zip(s.chars()).all(|(a, b)| *a == b)
+                };
+                match state {
+                    State::Code => {
+                        let line_start = chars[..i].iter().all(|c| c.is_whitespace());
+                        if starts("//")
+                            || (line_start && starts("#") && !starts("#[") && !starts("#!"))
+                        {
+                            break;
+                        }
+                        let after_ident =
+                            i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_');
+                        let raw_hashes = rest[1..].iter().take_while(|c| **c == '#').count();
+                        if starts("/*") {
+                            state = State::BlockComment;
+                            i += 2;
+                        } else if starts("\"\"\"") || starts("'''") {
+                            state = State::Triple(rest[0]);
+                            i += 3;
+                        } else if rest[0] == 'r'
+                            && !after_ident
+                            && rest.get(1 + raw_hashes) == Some(&'"')
+                        {
+                            state = State::Raw(raw_hashes);
+                            i += 2 + raw_hashes;
+                        } else if rest[0] == '"' || rest[0] == '`' {
+                            state = State::Quoted(rest[0]);
+                            i += 1;
+                        } else if starts("<<")
+                            && let Some(delimiter) = heredoc_delimiter(&rest[2..])
+                        {
+                            heredoc = Some(delimiter);
+                            i += 2;
+                        } else {
+                            i += 1;
+                        }
+                    }
+                    State::BlockComment => {
+                        if starts("*/") {
+                            state = State::Code;
+                            i += 2;
+                        } else {
+                            i += 1;
+                        }
+                    }
+                    State::Quoted(quote) => {
+                        if rest[0] == '\\' {
+                            i += 2;
+                        } else {
+                            if rest[0] == quote {
+                                state = State::Code;
+                            }
+                            i += 1;
+                        }
+                    }
+                    State::Triple(quote) => {
+                        if rest[0] == '\\' {
+                            i += 2;
+                        } else if rest.len() >= 3 && rest[..3].iter().all(|c| *c == quote) {
+                            state = State::Code;
+                            i += 3;
+                        } else {
+                            i += 1;
+                        }
+                    }
+                    State::Raw(hashes) => {
+                        if rest[0] == '"'
+                            && rest.len() > hashes
+                            && rest[1..=hashes].iter().all(|c| *c == '#')
+                        {
+                            state = State::Code;
+                            i += 1 + hashes;
+                        } else {
+                            i += 1;
+                        }
+                    }
+                    State::Heredoc(_) => break,
+                }
+            }
+            if let (State::Code, Some(delimiter)) = (&state, heredoc) {
+                state = State::Heredoc(delimiter);
+            }
+        }
+        result
+    }
+
+    /// Delimiter of a heredoc opened by the text following `<<`, such as
+    /// `EOF`, `-EOF`, `~SQL` or `'END'`. Only upper-case words count, which
+    /// keeps shifts like `x << y` out.
+    fn heredoc_delimiter(rest: &[char]) -> Option<String> {
+        let rest = rest
+            .strip_prefix(&['-'])
+            .or(rest.strip_prefix(&['~']))
+            .unwrap_or(rest);
+        let rest = rest
+            .strip_prefix(&['\''])
+            .or(rest.strip_prefix(&['"']))
+            .unwrap_or(rest);
+        let word: String = rest
+            .iter()
+            .take_while(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || **c == '_')
+            .collect();
+        word.starts_with(|c: char| c.is_ascii_uppercase())
+            .then_some(word)
+    }
+
+    /// Rewrite leading whitespace between tabs and spaces. Alignment spaces
+    /// after tab indentation, and lines inside multi-line strings, are left
+    /// alone.
+    pub fn convert_indentation(text: &str, indent: Indent, tab_width: Option<usize>) -> String {
+        let mut out = String::with_capacity(text.len());
+        // Width of one indent level in the incoming text when it uses spaces
+        let source_width = match indent {
+            Indent::Tabs => tab_width
+                .or_else(|| infer_indent(text).and_then(space_width))
+                .unwrap_or(4),
+            Indent::Spaces(n) => n,
+        };
+
+        let in_string = string_lines(text);
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let body = line.trim_start_matches([' ', '\t']);
+            let leading = &line[..line.len() - body.len()];
+            if body.is_empty() || in_string[i] {
+                out.push_str(line);
+                continue;
+            }
+            match indent {
+                Indent::Tabs if !leading.contains('\t') && !leading.is_empty() => {
+                    let spaces = leading.len();
+                    out.push_str(&"\t".repeat(spaces / source_width));
+                    out.push_str(&" ".repeat(spaces % source_width));
+                }
+                Indent::Spaces(n) if leading.contains('\t') => {
+                    for c in leading.chars() {
+                        if c == '\t' {
+                            out.push_str(&" ".repeat(n));
+                        } else {
+                            out.push(c);
+                        }
+                    }
+                }
+                _ => out.push_str(leading),
+            }
+            out.push_str(body);
+        }
+        out
+    }
+
+    /// Infer the indentation of existing text from its leading whitespace.
+    pub fn infer_indent(text: &str) -> Option<Indent> {
+        let (mut tab_lines, mut space_lines) = (0usize, 0usize);
+        let mut deltas = [0usize; 9];
+        let mut previous = 0usize;
+        let mut in_block_comment = false;
+
+        for line in text.lines() {
+            let body = line.trim_start_matches([' ', '\t']);
+            if body.is_empty() {
+                continue;
+            }
+            // Continuation lines of `/* ... */` comments are aligned with " *"
+            if in_block_comment || body.starts_with('*') {
+                in_block_comment = !body.contains("*/") && in_block_comment;
+                continue;
+            }
+            if body.starts_with("/*") && !body.contains("*/") {
+                in_block_comment = true;
+            }
+
+            let leading = &line[..line.len() - body.len()];
+            if leading.starts_with('\t') {
+                tab_lines += 1;
+                continue;
+            }
+            let width = leading.len();
+            if width > 0 {
+                space_lines += 1;
+            }
+            if width > previous && width - previous <= 8 {
+                deltas[width - previous] += 1;
+            }
+            previous = width;
+        }
+
+        if tab_lines + space_lines < MIN_INDENTED_LINES {
+            return None;
+        }
+        if tab_lines > space_lines {
+            return Some(Indent::Tabs);
+        }
+        // The most common indentation step wins; ties favour the wider step
+        // since nested blocks also produce multiples of the narrower one
+        let (step, count) = deltas
+            .iter()
+            .enumerate()
+            .skip(2)
+            .max_by_key(|(step, count)| (**count, *step))?;
+        (*count > 0).then_some(Indent::Spaces(step))
+    }
+
+    /// Infer the dominant string quote of existing text.
+    pub fn infer_quote(text: &str) -> Option<Quote> {
+        static DOUBLE: once_cell::sync::Lazy<Regex> =
+            once_cell::sync::Lazy::new(|| Regex::new(r#""(?:[^"\\\n]|\\.)*""#).unwrap());
+        static SINGLE: once_cell::sync::Lazy<Regex> =
+            once_cell::sync::Lazy::new(|| Regex::new(r"'(?:[^'\\\n]|\\.)*'").unwrap());
+
+        let mut double = 0usize;
+        let mut single = 0usize;
+        for line in text.lines() {
+            let code = line.trim_start();
+            if code.starts_with("//") || code.starts_with('#') {
+                continue;
+            }
+            double += DOUBLE.find_iter(line).count();
+            single += SINGLE.find_iter(line).count();
+        }
+
+        if double + single < MIN_QUOTED_STRINGS {
+            return None;
+        }
+        if double >= single * 2 {
+            Some(Quote::Double)
+        } else if single >= double * 2 {
+            Some(Quote::Single)
+        } else {
+            None
+        }
+    }
+
+    fn detected<T>(source: &str, value: T) -> Detected<T> {
+        Detected {
+            value,
+            source: source.to_string(),
+        }
+    }
+
+    fn space_width(indent: Indent) -> Option<usize> {
+        match indent {
+            Indent::Spaces(n) => Some(n),
+            Indent::Tabs => None,
+        }
+    }
+
+    fn display_width(line: &str, tab_width: usize) -> usize {
+        line.chars()
+            .map(|c| if c == '\t' { tab_width } else { 1 })
+            .sum()
+    }
+
+    /// Languages where single and double quoted strings are interchangeable,
+    /// so a quote preference is meaningful.
+    fn has_flexible_quotes(path: &Path) -> bool {
+        matches!(
+            extension(path).as_str(),
+            "js" | "jsx"
+                | "mjs"
+                | "cjs"
+                | "ts"
+                | "tsx"
+                | "vue"
+                | "svelte"
+                | "py"
+                | "pyi"
+                | "rb"
+                | "php"
+                | "dart"
+                | "lua"
+                | "coffee"
+                | "scss"
+                | "less"
+        )
+    }
+
+    fn is_makefile(path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        matches!(name, "Makefile" | "makefile" | "GNUmakefile") || extension(path) == "mk"
+    }
+
+    fn extension(path: &Path) -> String {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase()
+    }
+
+    /// Formatter config file names and the pattern extracting their line length.
+    type ConfigFiles<'a> = &'a [(&'a str, &'a Regex)];
+
+    /// Line length configured for the file's formatter, searching parent
+    /// directories up to the repository root.
+    fn project_line_length(path: &Path) -> Option<(usize, String)> {
+        static RUSTFMT: once_cell::sync::Lazy<Regex> =
+            once_cell::sync::Lazy::new(|| Regex::new(r"(?m)^\s*max_width\s*=\s*(\d+)").unwrap());
+        static PRETTIER: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
+            Regex::new(r#"(?m)["']?printWidth["']?\s*[:=]\s*(\d+)"#).unwrap()
+        });
+        static PYPROJECT: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
+            Regex::new(r"(?m)^\s*line[-_]length\s*=\s*(\d+)").unwrap()
+        });
+        static FLAKE8: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
+            Regex::new(r"(?m)^\s*max[-_]line[-_]length\s*=\s*(\d+)").unwrap()
+        });
+
+        let (candidates, default): (ConfigFiles, Option<(usize, &str)>) =
+            match extension(path).as_str() {
+                "rs" => (
+                    &[("rustfmt.toml", &RUSTFMT), (".rustfmt.toml", &RUSTFMT)],
+                    Some((100, "rustfmt default")),
+                ),
+                "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "vue" | "svelte" | "css" | "scss"
+                | "less" | "json" | "md" => (
+                    &[
+                        (".prettierrc", &PRETTIER),
+                        (".prettierrc.json", &PRETTIER),
+                        (".prettierrc.yaml", &PRETTIER),
+                        (".prettierrc.yml", &PRETTIER),
+                        (".prettierrc.toml", &PRETTIER),
+                        ("prettier.config.js", &PRETTIER),
+                        (".prettierrc.js", &PRETTIER),
+                    ],
+                    None,
+                ),
+                "py" | "pyi" => (
+                    &[
+                        ("pyproject.toml", &PYPROJECT),
+                        ("ruff.toml", &PYPROJECT),
+                        (".ruff.toml", &PYPROJECT),
+                        ("setup.cfg", &FLAKE8),
+                        ("tox.ini", &FLAKE8),
+                        (".flake8", &FLAKE8),
+                    ],
+                    None,
+                ),
+                _ => return None,
+            };
+
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir().ok()?.join(path)
+        };
+        let mut dir = absolute.parent();
+        while let Some(current) = dir {
+            for (name, pattern) in candidates {
+                let Ok(text) = fs::read_to_string(current.join(name)) else {
+                    continue;
+                };
+                if let Some(value) = pattern
+                    .captures(&text)
+                    .and_then(|c| c[1].parse::<usize>().ok())
+                {
+                    return Some((value, name.to_string()));
+                }
+            }
+            // Stop at the repository root
+            if current.join(".git").exists() {
+                break;
+            }
+            dir = current.parent();
+        }
+        default.map(|(value, source)| (value, source.to_string()))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_infer_indent_and_quotes() {
+            let js = "function a() {\n  if (x) {\n    return 'a';\n  }\n  const b = 'b';\n  const c = 'c';\n  return 'd';\n}\n";
+            assert_eq!(infer_indent(js), Some(Indent::Spaces(2)));
+            assert_eq!(infer_quote(js), Some(Quote::Single));
+
+            let go = "func main() {\n\tif x {\n\t\ty()\n\t}\n}\n";
+            assert_eq!(infer_indent(go), Some(Indent::Tabs));
+            assert_eq!(infer_indent("one line\n"), None);
+        }
+
+        #[test]
+        fn test_conform_converts_indentation_and_flags_long_lines() {
+            let dir = tempfile::TempDir::new().unwrap();
+            fs::write(
+                dir.path().join(".editorconfig"),
+                "root = true\n[*.go]\nindent_style = tab\ntab_width = 4\nmax_line_length = 20\n",
+            )
+            .unwrap();
+            let conventions = Conventions::detect(&dir.path().join("main.go"), None);
+            assert_eq!(conventions.indent.as_ref().unwrap().value, Indent::Tabs);
+
+            let (text, warnings) =
+                conventions.conform("if x {\n    y()\n        z := \"a long string here\"\n}");
+            assert_eq!(text, "if x {\n\ty()\n\t\tz := \"a long string here\"\n}");
+            assert_eq!(warnings.len(), 2);
+        }
+
+        #[test]
+        fn test_conform_leaves_strings_and_mixed_text_alone() {
+            let conventions = Conventions {
+                indent: Some(detected("inferred", Indent::Tabs)),
+                tab_width: Some(4),
+                ..Default::default()
+            };
+
+            // Lines inside a triple-quoted string and a heredoc keep their spaces
+            let python =
+                "def f():\n    q = \"\"\"\n    SELECT *\n    \"\"\"\n    run(\"\"\"x\"\"\")";
+            let (text, _) = conventions.conform(python);
+            assert_eq!(
+                text,
+                "def f():\n\tq = \"\"\"\n    SELECT *\n    \"\"\"\n\trun(\"\"\"x\"\"\")"
+            );
+            let shell = "main() {\n    cat <<EOF\n    indented\nEOF\n    echo $((1 << 2))\n}";
+            let (text, _) = conventions.conform(shell);
+            assert_eq!(
+                text,
+                "main() {\n\tcat <<EOF\n    indented\nEOF\n\techo $((1 << 2))\n}"
+            );
+            let rust = "let s = r#\"\n    raw \"quoted\"\n\"#;\n    done();";
+            assert_eq!(
+                conventions.conform(rust).0,
+                "let s = r#\"\n    raw \"quoted\"\n\"#;\n\tdone();"
+            );
+
+            // Already tab-indented text is untouched; mixed text is reported
+            let (text, warnings) = conventions.conform("if x {\n\ty()\n}");
+            assert_eq!(text, "if x {\n\ty()\n}");
+            assert!(warnings.is_empty());
+            let mixed = "if x {\n\ty()\n    z()\n}";
+            let (text, warnings) = conventions.conform(mixed);
+            assert_eq!(text, mixed);
+            assert!(
+                warnings[0].contains("mixes tabs and spaces"),
+                "{:?}",
+                warnings
+            );
+        }
+
+        #[test]
+        fn test_check_reports_without_rewriting() {
+            let conventions = Conventions {
+                indent: Some(detected("inferred", Indent::Tabs)),
+                max_line_length: Some(detected("rustfmt.toml", 10)),
+                tab_width: Some(4),
+                ..Default::default()
+            };
+            let warnings = conventions.check("if x {\n    y()\n}");
+            assert_eq!(
+                warnings,
+                vec!["the new text is indented with spaces; the file uses tabs".to_string()]
+            );
+            assert_eq!(conventions.check("if x {\n\ty()\n}"), Vec::<String>::new());
+            assert_eq!(conventions.check("a_very_long_line();").len(), 1);
+        }
+
+        #[test]
+        fn test_tabs_to_spaces() {
+            let converted = convert_indentation("a\n\tb\n\t\tc", Indent::Spaces(2), None);
+            assert_eq!(converted, "a\n  b\n    c");
+        }
+    }
+}
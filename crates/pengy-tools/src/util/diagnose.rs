@@ -0,0 +1,438 @@
+pub mod diagnose {
+    //! Turn a pasted error or stack trace into a root-cause analysis request:
+    //! frames are mapped to workspace files, and the surrounding code, recent
+    //! `git blame` for the failing lines and likely related tests are gathered
+    //! into a single prompt.
+
+    use crate::util::workspace_files::workspace_files;
+    use once_cell::sync::Lazy;
+    use regex::Regex;
+    use std::cell::OnceCell;
+    use std::collections::HashSet;
+    use std::error::Error;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// Frames included in the report; traces are usually deepest-first, and
+    /// the first workspace frames carry the most signal.
+    const MAX_FRAMES: usize = 6;
+    /// Lines of code shown on each side of a frame's line.
+    const CONTEXT_LINES: usize = 8;
+    /// Lines of blame shown on each side of a frame's line.
+    const BLAME_LINES: usize = 2;
+    const MAX_RELATED_TESTS: usize = 6;
+    const MAX_SCAN_FILES: usize = 20_000;
+    const MAX_SCAN_BYTES: u64 = 512 * 1024;
+    /// Dependency and toolchain directories whose frames never map to
+    /// workspace code, even when a suffix happens to match.
+    const THIRD_PARTY_DIRS: &[&str] = &[
+        ".cargo",
+        ".rustup",
+        "node_modules",
+        "site-packages",
+        "dist-packages",
+        "rustc",
+    ];
+
+    // Python: File "app/models.py", line 42, in save
+    static PYTHON: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"File "([^"]+)", line (\d+)(?:, in ([\w<>.]+))?"#).unwrap());
+    // Java/Kotlin/Scala: at com.acme.Service.run(Service.java:17)
+    static JVM: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"at ([\w$.<>]+)\(([\w$-]+\.(?:java|kt|scala|groovy)):(\d+)\)").unwrap()
+    });
+    // Everything else: path:line[:col], as printed by Rust, Node, Go, gcc, tsc...
+    static GENERIC: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?:^|[\s(\[`'])((?:[A-Za-z]:)?[\w./\\@+-]*\.[A-Za-z0-9]{1,10}):(\d+)(?::(\d+))?",
+        )
+        .unwrap()
+    });
+    // Function names in Rust/Node/Go frames: "at fn_name (" or "N: crate::fn_name"
+    static FUNCTION: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?:at\s+|\d+:\s+)([A-Za-z_][\w:.<>$]*)\s*(?:\(|$)").unwrap());
+
+    /// A stack frame resolved to a file in the workspace.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Frame {
+        pub path: PathBuf,
+        pub line: usize,
+        pub function: Option<String>,
+    }
+
+    /// Frames of `trace` that point at files inside `workspace`, in trace
+    /// order, deduplicated and capped.
+    pub fn parse_frames(trace: &str, workspace: &Path) -> Vec<Frame> {
+        frames_in(trace, workspace, &OnceCell::new())
+    }
+
+    /// `parse_frames` with the workspace file list shared with the caller;
+    /// it is only collected if a JVM frame needs a lookup by name.
+    fn frames_in(trace: &str, workspace: &Path, files: &OnceCell<Vec<PathBuf>>) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending_function: Option<String> = None;
+        let mut push = |frames: &mut Vec<Frame>, path: PathBuf, line: usize, function| {
+            if line > 0 && seen.insert((path.clone(), line)) {
+                frames.push(Frame {
+                    path,
+                    line,
+                    function,
+                });
+            }
+        };
+
+        for text in trace.lines() {
+            if frames.len() >= MAX_FRAMES {
+                break;
+            }
+            if let Some(caps) = PYTHON.captures(text) {
+                if let Some(path) = resolve(workspace, &caps[1]) {
+                    let function = caps.get(3).map(|m| m.as_str().to_string());
+                    push(&mut frames, path, caps[2].parse().unwrap_or(0), function);
+                }
+                continue;
+            }
+            if let Some(caps) = JVM.captures(text) {
+                let qualified = caps[1].to_string();
+                let package_dir = qualified
+                    .rsplitn(3, '.')
+                    .nth(2)
+                    .map(|pkg| pkg.replace('.', "/"));
+                let files = files.get_or_init(|| scan(workspace));
+                if let Some(path) = find_by_name(files, &caps[2], package_dir.as_deref()) {
+                    push(
+                        &mut frames,
+                        path,
+                        caps[3].parse().unwrap_or(0),
+                        Some(qualified),
+                    );
+                }
+                continue;
+            }
+
+            let mut matched = false;
+            for caps in GENERIC.captures_iter(text) {
+                if let Some(path) = resolve(workspace, &caps[1]) {
+                    let function = pending_function
+                        .take()
+                        .or_else(|| FUNCTION.captures(text).map(|c| c[1].to_string()));
+                    push(&mut frames, path, caps[2].parse().unwrap_or(0), function);
+                    matched = true;
+                }
+            }
+            // Rust backtraces print the function on the line before its location
+            if !matched {
+                pending_function = FUNCTION.captures(text).map(|c| c[1].to_string());
+            }
+        }
+        frames
+    }
+
+    /// Map a path from a trace onto an existing file in the workspace. Paths
+    /// from other machines (CI, containers) are matched by their longest
+    /// suffix that exists locally.
+    fn resolve(workspace: &Path, raw: &str) -> Option<PathBuf> {
+        let raw = raw.trim_start_matches("file://").replace('\\', "/");
+        let candidate = Path::new(&raw);
+        let workspace = workspace
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.to_path_buf());
+
+        let inside = |path: PathBuf| -> Option<PathBuf> {
+            let path = path.canonicalize().ok()?;
+            (path.starts_with(&workspace) && path.is_file()).then_some(path)
+        };
+
+        if candidate.is_absolute() {
+            if let Some(path) = inside(candidate.to_path_buf()) {
+                return Some(path);
+            }
+        } else if let Some(path) = inside(workspace.join(candidate)) {
+            return Some(path);
+        }
+
+        let parts: Vec<&str> = raw.split('/').filter(|p| !p.is_empty()).collect();
+        if parts.iter().any(|p| THIRD_PARTY_DIRS.contains(p)) {
+            return None;
+        }
+        (1..parts.len())
+            .map(|skip| parts[skip..].join("/"))
+            .find_map(|suffix| inside(workspace.join(suffix)))
+    }
+
+    /// Workspace files considered by `find_by_name` and `related_tests`.
+    /// Collected once per diagnosis.
+    fn scan(workspace: &Path) -> Vec<PathBuf> {
+        workspace_files::collect_files(workspace, MAX_SCAN_FILES, MAX_SCAN_BYTES)
+    }
+
+    /// Locate a JVM source file by name, preferring one under its package
+    /// directory.
+    fn find_by_name(files: &[PathBuf], name: &str, package_dir: Option<&str>) -> Option<PathBuf> {
+        let matches: Vec<&PathBuf> = files
+            .iter()
+            .filter(|p| p.file_name().and_then(|n| n.to_str()) == Some(name))
+            .collect();
+        let preferred = package_dir.and_then(|dir| {
+            matches
+                .iter()
+                .find(|p| p.to_string_lossy().replace('\\', "/").contains(dir))
+                .copied()
+        });
+        preferred
+            .or_else(|| matches.first().copied())
+            .and_then(|p| p.canonicalize().ok())
+    }
+
+    /// Numbered lines around `line`, marking the failing one.
+    fn code_region(path: &Path, line: usize) -> Option<String> {
+        let content = fs::read_to_string(path).ok()?;
+        let lines: Vec<&str> = content.lines().collect();
+        if line == 0 || line > lines.len() {
+            return None;
+        }
+        let start = line.saturating_sub(CONTEXT_LINES).max(1);
+        let end = (line + CONTEXT_LINES).min(lines.len());
+        Some(
+            (start..=end)
+                .map(|n| {
+                    let marker = if n == line { ">" } else { " " };
+                    format!("{}{:>5} | {}", marker, n, lines[n - 1])
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
+    fn blame(workspace: &Path, path: &Path, line: usize) -> Option<String> {
+        let start = line.saturating_sub(BLAME_LINES).max(1);
+        let end = line + BLAME_LINES;
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(workspace)
+            .args(["blame", "--date=short", "-L"])
+            .arg(format!("{},{}", start, end))
+            .arg("--")
+            .arg(path)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string();
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Test files that are likely to exercise `path` or `function`: files
+    /// named after the module by common test conventions, plus test files
+    /// that mention the function. `files` is the workspace file list, as
+    /// collected by `workspace_files::collect_files`.
+    pub fn related_tests(
+        workspace: &Path,
+        files: &[PathBuf],
+        path: &Path,
+        function: Option<&str>,
+    ) -> Vec<PathBuf> {
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        if stem.is_empty() {
+            return Vec::new();
+        }
+        let conventional = [
+            format!("test_{}", stem),
+            format!("{}_test", stem),
+            format!("{}_tests", stem),
+            format!("{}.test", stem),
+            format!("{}.spec", stem),
+            format!("{}Test", stem),
+            format!("{}Tests", stem),
+        ];
+        // The last path segment of a qualified function name, e.g. `save`
+        let symbol = function
+            .and_then(|f| f.rsplit([':', '.']).next())
+            .filter(|s| s.len() > 2 && !s.starts_with('<'))
+            .map(|s| s.to_string());
+
+        let mut related = Vec::new();
+        for file in files {
+            if related.len() >= MAX_RELATED_TESTS {
+                break;
+            }
+            if file == path {
+                continue;
+            }
+            let relative = file.strip_prefix(workspace).unwrap_or(file);
+            let relative_text = relative.to_string_lossy().replace('\\', "/");
+            let file_stem = file
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            let in_test_dir = relative_text
+                .split('/')
+                .any(|part| matches!(part, "tests" | "test" | "__tests__" | "spec"));
+
+            if conventional.iter().any(|name| name == file_stem)
+                || (in_test_dir && file_stem == stem)
+            {
+                related.push(file.clone());
+                continue;
+            }
+            let looks_like_test = in_test_dir
+                || file_stem.starts_with("test_")
+                || file_stem.ends_with("_test")
+                || file_stem.ends_with(".test")
+                || file_stem.ends_with(".spec");
+            if looks_like_test
+                && let Some(symbol) = &symbol
+                && fs::read_to_string(file)
+                    .map(|text| text.contains(symbol.as_str()))
+                    .unwrap_or(false)
+            {
+                related.push(file.clone());
+            }
+        }
+        related
+    }
+
+    /// Build the root-cause analysis prompt for `trace`.
+    pub fn build_prompt(trace: &str, workspace: &Path) -> Result<String, Box<dyn Error>> {
+        let trace = trace.trim();
+        if trace.is_empty() {
+            return Err("no error or stack trace to diagnose".into());
+        }
+        let workspace = workspace
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.to_path_buf());
+        let files = OnceCell::new();
+        let frames = frames_in(trace, &workspace, &files);
+        let display = |path: &Path| {
+            path.strip_prefix(&workspace)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+
+        let mut out = String::from(
+            "Diagnose the following error. Identify the root cause (not just the line that failed), explain the chain of events that leads to it, point to the exact code responsible, and propose a minimal fix plus a test that would catch it. Use the tools to read more code if the context below is not enough; do not modify files until the cause is confirmed.\n\n",
+        );
+        out.push_str(&format!("## Error\n```\n{}\n```\n", trace));
+
+        if frames.is_empty() {
+            out.push_str(
+                "\n(No frames in the trace could be mapped to files in this workspace; search for the error message and the functions it mentions.)\n",
+            );
+            return Ok(out);
+        }
+
+        let mut tests: Vec<PathBuf> = Vec::new();
+        for (index, frame) in frames.iter().enumerate() {
+            let name = display(&frame.path);
+            out.push_str(&format!(
+                "\n## Frame {}: {}:{}{}\n",
+                index + 1,
+                name,
+                frame.line,
+                frame
+                    .function
+                    .as_ref()
+                    .map(|f| format!(" in {}", f))
+                    .unwrap_or_default()
+            ));
+            match code_region(&frame.path, frame.line) {
+                Some(region) => out.push_str(&format!("```\n{}\n```\n", region)),
+                None => out.push_str("(line is outside the current file; the code may have changed since the error)\n"),
+            }
+            if let Some(blame) = blame(&workspace, &frame.path, frame.line) {
+                out.push_str(&format!(
+                    "Recent history (git blame):\n```\n{}\n```\n",
+                    blame
+                ));
+            }
+            let files = files.get_or_init(|| scan(&workspace));
+            for test in related_tests(&workspace, files, &frame.path, frame.function.as_deref()) {
+                if !tests.contains(&test) {
+                    tests.push(test);
+                }
+            }
+        }
+
+        if !tests.is_empty() {
+            out.push_str("\n## Related tests\n");
+            for test in tests.iter().take(MAX_RELATED_TESTS) {
+                out.push_str(&format!("- {}\n", display(test)));
+            }
+        }
+        Ok(out)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn workspace() -> tempfile::TempDir {
+            let dir = tempfile::TempDir::new().unwrap();
+            fs::create_dir_all(dir.path().join("app")).unwrap();
+            fs::create_dir_all(dir.path().join("src")).unwrap();
+            fs::create_dir_all(dir.path().join("tests")).unwrap();
+            fs::write(
+                dir.path().join("app/models.py"),
+                "class User:\n    def save(self):\n        raise ValueError('boom')\n",
+            )
+            .unwrap();
+            fs::write(
+                dir.path().join("tests/test_models.py"),
+                "def test_save():\n    User().save()\n",
+            )
+            .unwrap();
+            fs::write(
+                dir.path().join("src/main.rs"),
+                "fn main() {\n    run();\n}\n",
+            )
+            .unwrap();
+            dir
+        }
+
+        #[test]
+        fn test_parse_python_and_rust_frames() {
+            let dir = workspace();
+            let python = "Traceback (most recent call last):\n  File \"/ci/build/app/models.py\", line 3, in save\n  File \"/usr/lib/python3.12/json/__init__.py\", line 10, in dumps\nValueError: boom";
+            let frames = parse_frames(python, dir.path());
+            assert_eq!(frames.len(), 1);
+            assert!(frames[0].path.ends_with("app/models.py"));
+            assert_eq!(frames[0].line, 3);
+            assert_eq!(frames[0].function.as_deref(), Some("save"));
+
+            let rust = "thread 'main' panicked at src/main.rs:2:5:\nexplicit panic\n   0: demo::run\n             at ./src/main.rs:2:5";
+            let frames = parse_frames(rust, dir.path());
+            assert_eq!(frames.len(), 1);
+            assert!(frames[0].path.ends_with("src/main.rs"));
+        }
+
+        #[test]
+        fn test_build_prompt_includes_code_and_related_tests() {
+            let dir = workspace();
+            let prompt = build_prompt(
+                "File \"app/models.py\", line 3, in save\nValueError: boom",
+                dir.path(),
+            )
+            .unwrap();
+            assert!(
+                prompt.contains(">    3 |         raise ValueError('boom')"),
+                "{}",
+                prompt
+            );
+            assert!(
+                prompt.contains("## Related tests\n- tests/test_models.py"),
+                "{}",
+                prompt
+            );
+        }
+    }
+}
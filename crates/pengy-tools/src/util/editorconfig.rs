@@ -0,0 +1,306 @@
+pub mod editorconfig {
+    //! Minimal EditorConfig resolver.
+    //!
+    //! Walks from a file's directory up to the nearest `.editorconfig` with
+    //! `root = true` (or the filesystem root), applying matching sections from
+    //! the outermost file to the innermost so closer settings win. Supports the
+    //! usual glob syntax: `*`, `**`, `?`, `[abc]`, `{a,b}` and `{1..3}`.
+
+    use regex::Regex;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    const FILE_NAME: &str = ".editorconfig";
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum IndentStyle {
+        Tab,
+        Space,
+    }
+
+    /// Properties resolved for a single file. Unset or `unset` properties are
+    /// `None`.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct EditorConfig {
+        pub indent_style: Option<IndentStyle>,
+        pub indent_size: Option<usize>,
+        pub tab_width: Option<usize>,
+        /// `lf`, `crlf` or `cr`.
+        pub end_of_line: Option<String>,
+        /// `utf-8`, `utf-8-bom`, `utf-16le`, `utf-16be` or `latin1`.
+        pub charset: Option<String>,
+        pub insert_final_newline: Option<bool>,
+        pub trim_trailing_whitespace: Option<bool>,
+        pub max_line_length: Option<usize>,
+        /// Non-standard but widely used: `single`, `double` or `auto`.
+        pub quote_type: Option<String>,
+        /// Innermost `.editorconfig` that contributed a setting.
+        pub source: Option<PathBuf>,
+    }
+
+    impl EditorConfig {
+        /// True if no property applies to the file.
+        pub fn is_empty(&self) -> bool {
+            self.source.is_none()
+        }
+
+        /// Indent width in columns, following `indent_size = tab`.
+        pub fn indent_width(&self) -> Option<usize> {
+            self.indent_size.or(self.tab_width)
+        }
+
+        fn apply(&mut self, key: &str, value: &str) {
+            let value = value.to_lowercase();
+            let unset = value == "unset";
+            let number = || value.parse::<usize>().ok().filter(|n| *n > 0);
+            let flag = || match value.as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            };
+            match key {
+                "indent_style" => {
+                    self.indent_style = match value.as_str() {
+                        "tab" => Some(IndentStyle::Tab),
+                        "space" => Some(IndentStyle::Space),
+                        _ => None,
+                    }
+                }
+                "indent_size" => {
+                    self.indent_size = if value == "tab" {
+                        self.tab_width
+                    } else {
+                        number()
+                    }
+                }
+                "tab_width" => self.tab_width = number(),
+                "end_of_line" => {
+                    self.end_of_line = (!unset && matches!(value.as_str(), "lf" | "crlf" | "cr"))
+                        .then(|| value.clone())
+                }
+                "charset" => self.charset = (!unset).then(|| value.clone()),
+                "insert_final_newline" => self.insert_final_newline = flag(),
+                "trim_trailing_whitespace" => self.trim_trailing_whitespace = flag(),
+                "max_line_length" => self.max_line_length = number(),
+                "quote_type" => self.quote_type = (!unset).then(|| value.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Resolve the EditorConfig properties that apply to `path`.
+    pub fn resolve(path: &Path) -> EditorConfig {
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()
+                .map(|cwd| cwd.join(path))
+                .unwrap_or_else(|_| path.to_path_buf())
+        };
+
+        // Collect config files from the innermost directory outwards
+        let mut configs = Vec::new();
+        let mut dir = absolute.parent();
+        while let Some(current) = dir {
+            let candidate = current.join(FILE_NAME);
+            if let Ok(text) = fs::read_to_string(&candidate) {
+                let parsed = parse(&text);
+                let is_root = parsed.root;
+                configs.push((candidate, parsed));
+                if is_root {
+                    break;
+                }
+            }
+            dir = current.parent();
+        }
+
+        let mut resolved = EditorConfig::default();
+        for (config_path, parsed) in configs.iter().rev() {
+            let base = config_path.parent().unwrap_or(Path::new("/"));
+            let Ok(relative) = absolute.strip_prefix(base) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            for section in &parsed.sections {
+                if section_matches(&section.glob, &relative) {
+                    for (key, value) in &section.properties {
+                        resolved.apply(key, value);
+                    }
+                    resolved.source = Some(config_path.clone());
+                }
+            }
+        }
+        resolved
+    }
+
+    struct Section {
+        glob: String,
+        properties: Vec<(String, String)>,
+    }
+
+    struct Parsed {
+        root: bool,
+        sections: Vec<Section>,
+    }
+
+    fn parse(text: &str) -> Parsed {
+        let mut parsed = Parsed {
+            root: false,
+            sections: Vec::new(),
+        };
+        for raw in text.lines() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                parsed.sections.push(Section {
+                    glob: line[1..line.len() - 1].to_string(),
+                    properties: Vec::new(),
+                });
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_string();
+            match parsed.sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                None if key == "root" => parsed.root = value.eq_ignore_ascii_case("true"),
+                None => {}
+            }
+        }
+        parsed
+    }
+
+    /// Match a section glob against a path relative to the config's directory.
+    fn section_matches(glob: &str, relative: &str) -> bool {
+        let glob = if let Some(stripped) = glob.strip_prefix('/') {
+            stripped.to_string()
+        } else if glob.contains('/') {
+            glob.to_string()
+        } else {
+            format!("**/{}", glob)
+        };
+        Regex::new(&format!("^{}$", glob_to_regex(&glob)))
+            .map(|re| re.is_match(relative))
+            .unwrap_or(false)
+    }
+
+    fn glob_to_regex(glob: &str) -> String {
+        let chars: Vec<char> = glob.chars().collect();
+        let mut out = String::new();
+        let mut i = 0;
+        let mut brace_depth = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            match c {
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    // `**/` may also match zero directories
+                    if chars.get(i + 2) == Some(&'/') {
+                        out.push_str("(?:.*/)?");
+                        i += 3;
+                    } else {
+                        out.push_str(".*");
+                        i += 2;
+                    }
+                    continue;
+                }
+                '*' => out.push_str("[^/]*"),
+                '?' => out.push_str("[^/]"),
+                '[' => {
+                    if let Some(end) = chars[i + 1..].iter().position(|c| *c == ']') {
+                        let class: String = chars[i + 1..i + 1 + end].iter().collect();
+                        let class = class.replacen('!', "^", usize::from(class.starts_with('!')));
+                        out.push('[');
+                        out.push_str(&class.replace('\\', "\\\\"));
+                        out.push(']');
+                        i += end + 2;
+                        continue;
+                    }
+                    out.push_str("\\[");
+                }
+                '{' => {
+                    if let Some(end) = chars[i + 1..].iter().position(|c| *c == '}') {
+                        let inner: String = chars[i + 1..i + 1 + end].iter().collect();
+                        if let Some((a, b)) = inner.split_once("..")
+                            && a.parse::<i64>().is_ok()
+                            && b.parse::<i64>().is_ok()
+                        {
+                            out.push_str("[+-]?\\d+");
+                            i += end + 2;
+                            continue;
+                        }
+                        if !inner.contains(',') {
+                            out.push_str(&regex::escape(&format!("{{{}}}", inner)));
+                            i += end + 2;
+                            continue;
+                        }
+                    }
+                    brace_depth += 1;
+                    out.push_str("(?:");
+                }
+                '}' if brace_depth > 0 => {
+                    brace_depth -= 1;
+                    out.push(')');
+                }
+                ',' if brace_depth > 0 => out.push('|'),
+                '\\' if i + 1 < chars.len() => {
+                    out.push_str(&regex::escape(&chars[i + 1].to_string()));
+                    i += 2;
+                    continue;
+                }
+                other => out.push_str(&regex::escape(&other.to_string())),
+            }
+            i += 1;
+        }
+        out
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_section_matching() {
+            assert!(section_matches("*", "src/main.rs"));
+            assert!(section_matches("*.{js,ts}", "web/app.ts"));
+            assert!(!section_matches("*.{js,ts}", "web/app.rs"));
+            assert!(section_matches("Makefile", "sub/Makefile"));
+            assert!(section_matches("/lib/**.js", "lib/a/b.js"));
+            assert!(!section_matches("/lib/**.js", "src/lib/b.js"));
+            assert!(section_matches(
+                "{package.json,.travis.yml}",
+                "package.json"
+            ));
+        }
+
+        #[test]
+        fn test_resolve_nested_configs() {
+            let dir = tempfile::TempDir::new().unwrap();
+            fs::write(
+                dir.path().join(".editorconfig"),
+                "root = true\n\n[*]\nindent_style = space\nindent_size = 4\nend_of_line = lf\n\n[*.go]\nindent_style = tab\n",
+            )
+            .unwrap();
+            fs::create_dir_all(dir.path().join("web")).unwrap();
+            fs::write(
+                dir.path().join("web/.editorconfig"),
+                "[*.ts]\nindent_size = 2\nquote_type = single\nmax_line_length = 100\n",
+            )
+            .unwrap();
+
+            let ts = resolve(&dir.path().join("web/app.ts"));
+            assert_eq!(ts.indent_style, Some(IndentStyle::Space));
+            assert_eq!(ts.indent_size, Some(2));
+            assert_eq!(ts.quote_type.as_deref(), Some("single"));
+            assert_eq!(ts.max_line_length, Some(100));
+            assert_eq!(ts.end_of_line.as_deref(), Some("lf"));
+
+            let go = resolve(&dir.path().join("main.go"));
+            assert_eq!(go.indent_style, Some(IndentStyle::Tab));
+            assert_eq!(go.indent_size, Some(4));
+        }
+    }
+}
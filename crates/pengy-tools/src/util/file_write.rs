@@ -0,0 +1,233 @@
+pub mod file_write {
+    //! Write files atomically while keeping the permissions and ownership of the
+    //! file being replaced, and apply chmod-style mode changes.
+    //!
+    //! Content is written to a temporary sibling file which receives the
+    //! original mode bits (and owner/group where the process is allowed to set
+    //! them) before being renamed over the target, so scripts keep their
+    //! executable bit and readers never observe a half-written file. Symlinks
+    //! are written through to the file they point at; hard-linked files and
+    //! files in directories where no sibling can be created are overwritten in
+    //! place instead, since a rename would detach them.
+
+    use std::error::Error;
+    use std::fs;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    /// Write `bytes` to `path`, preserving the existing file's permissions and
+    /// ownership. New files are created with the default permissions.
+    pub fn write_preserving(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+        let existing = fs::metadata(path).ok().filter(|m| m.is_file());
+        let Some(metadata) = existing else {
+            fs::write(path, bytes)?;
+            return Ok(());
+        };
+        // Replace the file a symlink points at, not the link itself.
+        let target = fs::canonicalize(path)?;
+        if link_count(&metadata) > 1 {
+            fs::write(&target, bytes)?;
+            return Ok(());
+        }
+
+        let tmp_path = temp_sibling(&target);
+        let Ok(mut tmp) = fs::File::create(&tmp_path) else {
+            fs::write(&target, bytes)?;
+            return Ok(());
+        };
+        let result = (|| -> Result<(), Box<dyn Error>> {
+            tmp.write_all(bytes)?;
+            tmp.sync_all()?;
+            fs::set_permissions(&tmp_path, metadata.permissions())?;
+            copy_ownership(&metadata, &tmp_path);
+            fs::rename(&tmp_path, &target)?;
+            Ok(())
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result
+    }
+
+    #[cfg(unix)]
+    fn link_count(metadata: &fs::Metadata) -> u64 {
+        use std::os::unix::fs::MetadataExt;
+        metadata.nlink()
+    }
+
+    #[cfg(not(unix))]
+    fn link_count(_metadata: &fs::Metadata) -> u64 {
+        1
+    }
+
+    fn temp_sibling(path: &Path) -> PathBuf {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".to_string());
+        path.with_file_name(format!(".{}.pengy-tmp-{}", name, std::process::id()))
+    }
+
+    #[cfg(unix)]
+    fn copy_ownership(metadata: &fs::Metadata, target: &Path) {
+        use std::os::unix::fs::MetadataExt;
+        // Only succeeds for root or when the ids already match; a failure just
+        // leaves the file owned by the current user.
+        let _ = std::os::unix::fs::chown(target, Some(metadata.uid()), Some(metadata.gid()));
+    }
+
+    #[cfg(not(unix))]
+    fn copy_ownership(_metadata: &fs::Metadata, _target: &Path) {}
+
+    /// Compute a new mode from a chmod-style spec: octal (`755`, `0644`) or
+    /// symbolic (`+x`, `u+x`, `go-w`, `a=r`), comma separated clauses allowed.
+    pub fn parse_mode(spec: &str, current: u32) -> Result<u32, String> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Err("mode cannot be empty".to_string());
+        }
+        if spec.chars().all(|c| c.is_ascii_digit()) {
+            return u32::from_str_radix(spec, 8)
+                .ok()
+                .filter(|m| *m <= 0o7777)
+                .ok_or_else(|| format!("invalid octal mode: {}", spec));
+        }
+
+        let mut mode = current & 0o7777;
+        for clause in spec.split(',') {
+            let op_idx = clause
+                .find(['+', '-', '='])
+                .ok_or_else(|| format!("invalid mode clause: {}", clause))?;
+            let (who, rest) = clause.split_at(op_idx);
+            let op = rest.chars().next().unwrap_or('+');
+            let perms = &rest[1..];
+
+            let mut who_mask = 0u32;
+            for c in who.chars() {
+                who_mask |= match c {
+                    'u' => 0o700,
+                    'g' => 0o070,
+                    'o' => 0o007,
+                    'a' => 0o777,
+                    _ => return Err(format!("invalid mode target '{}' in {}", c, clause)),
+                };
+            }
+            if who_mask == 0 {
+                who_mask = 0o777;
+            }
+
+            let mut perm_bits = 0u32;
+            for c in perms.chars() {
+                perm_bits |= match c {
+                    'r' => 0o444,
+                    'w' => 0o222,
+                    'x' => 0o111,
+                    _ => return Err(format!("invalid permission '{}' in {}", c, clause)),
+                };
+            }
+            let bits = perm_bits & who_mask;
+
+            mode = match op {
+                '+' => mode | bits,
+                '-' => mode & !bits,
+                _ => (mode & !who_mask) | bits,
+            };
+        }
+        Ok(mode)
+    }
+
+    /// True when going from `old` to `new` turns on any execute bit.
+    pub fn adds_execute(old: u32, new: u32) -> bool {
+        (new & !old) & 0o111 != 0
+    }
+
+    /// Current permission bits of `path`.
+    #[cfg(unix)]
+    pub fn current_mode(path: &Path) -> Result<u32, Box<dyn Error>> {
+        use std::os::unix::fs::PermissionsExt;
+        Ok(fs::metadata(path)?.permissions().mode() & 0o7777)
+    }
+
+    #[cfg(not(unix))]
+    pub fn current_mode(_path: &Path) -> Result<u32, Box<dyn Error>> {
+        Err("file modes are not supported on this platform".into())
+    }
+
+    /// Set the permission bits of `path`.
+    #[cfg(unix)]
+    pub fn set_mode(path: &Path, mode: u32) -> Result<(), Box<dyn Error>> {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn set_mode(_path: &Path, _mode: u32) -> Result<(), Box<dyn Error>> {
+        Err("file modes are not supported on this platform".into())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_mode_octal_and_symbolic() {
+            assert_eq!(parse_mode("755", 0o644).unwrap(), 0o755);
+            assert_eq!(parse_mode("0600", 0o644).unwrap(), 0o600);
+            assert_eq!(parse_mode("+x", 0o644).unwrap(), 0o755);
+            assert_eq!(parse_mode("u+x", 0o644).unwrap(), 0o744);
+            assert_eq!(parse_mode("go-r", 0o644).unwrap(), 0o600);
+            assert_eq!(parse_mode("a=r,u+w", 0o755).unwrap(), 0o644);
+            assert!(parse_mode("z+x", 0o644).is_err());
+            assert!(parse_mode("999", 0o644).is_err());
+        }
+
+        #[test]
+        fn test_adds_execute() {
+            assert!(adds_execute(0o644, 0o744));
+            assert!(!adds_execute(0o755, 0o644));
+            assert!(!adds_execute(0o644, 0o600));
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_write_preserving_keeps_mode() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let path = dir.path().join("script.sh");
+            fs::write(&path, "#!/bin/sh\necho old\n").unwrap();
+            set_mode(&path, 0o750).unwrap();
+
+            write_preserving(&path, b"#!/bin/sh\necho new\n").unwrap();
+
+            assert_eq!(fs::read_to_string(&path).unwrap(), "#!/bin/sh\necho new\n");
+            assert_eq!(current_mode(&path).unwrap(), 0o750);
+            assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_write_preserving_keeps_links() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let real = dir.path().join("real.txt");
+            let link = dir.path().join("link.txt");
+            let hard = dir.path().join("hard.txt");
+            fs::write(&real, "old\n").unwrap();
+            std::os::unix::fs::symlink(&real, &link).unwrap();
+
+            write_preserving(&link, b"via symlink\n").unwrap();
+            assert!(
+                fs::symlink_metadata(&link)
+                    .unwrap()
+                    .file_type()
+                    .is_symlink()
+            );
+            assert_eq!(fs::read_to_string(&real).unwrap(), "via symlink\n");
+
+            fs::hard_link(&real, &hard).unwrap();
+            write_preserving(&hard, b"via hard link\n").unwrap();
+            assert_eq!(fs::read_to_string(&real).unwrap(), "via hard link\n");
+            assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod conventions;
+pub mod diagnose;
+pub mod editorconfig;
+pub mod file_write;
+pub mod github_control;
+pub mod text_file;
+pub mod trash;
+pub mod workspace_files;
@@ -0,0 +1,588 @@
+pub mod text_file {
+    //! Read and write text files while remembering their on-disk encoding and
+    //! line endings, so tool edits round-trip without whole-file diffs.
+    //!
+    //! Files are decoded into a `String` whose line endings are normalized to
+    //! `\n`. Writing goes back through the same `TextFile`, which re-applies the
+    //! original encoding (UTF-8 with or without BOM, UTF-16 LE/BE) and line
+    //! ending style and reports anything it could not preserve.
+    //!
+    //! When an `.editorconfig` applies to the file its `end_of_line`, `charset`,
+    //! `insert_final_newline` and `indent_style` settings take precedence over
+    //! the detected format. Every adjustment, and any indentation the new
+    //! content gets wrong, is reported as a warning.
+
+    use std::error::Error;
+    use std::fs;
+    use std::path::Path;
+
+    use crate::util::editorconfig::editorconfig::{self, EditorConfig, IndentStyle};
+    use crate::util::file_write::file_write;
+
+    /// On-disk character encoding of a text file.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Encoding {
+        Utf8 { bom: bool },
+        Utf16Le { bom: bool },
+        Utf16Be { bom: bool },
+    }
+
+    impl Encoding {
+        /// Short human-readable label used in tool output.
+        pub fn label(&self) -> &'static str {
+            match self {
+                Encoding::Utf8 { bom: false } => "UTF-8",
+                Encoding::Utf8 { bom: true } => "UTF-8 with BOM",
+                Encoding::Utf16Le { bom: false } => "UTF-16LE",
+                Encoding::Utf16Le { bom: true } => "UTF-16LE with BOM",
+                Encoding::Utf16Be { bom: false } => "UTF-16BE",
+                Encoding::Utf16Be { bom: true } => "UTF-16BE with BOM",
+            }
+        }
+    }
+
+    /// Line terminator style used by a file.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum LineEnding {
+        Lf,
+        Crlf,
+        Cr,
+    }
+
+    impl LineEnding {
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                LineEnding::Lf => "\n",
+                LineEnding::Crlf => "\r\n",
+                LineEnding::Cr => "\r",
+            }
+        }
+
+        pub fn label(&self) -> &'static str {
+            match self {
+                LineEnding::Lf => "LF",
+                LineEnding::Crlf => "CRLF",
+                LineEnding::Cr => "CR",
+            }
+        }
+    }
+
+    /// Decoded text file plus the formatting needed to write it back unchanged.
+    #[derive(Debug, Clone)]
+    pub struct TextFile {
+        /// Decoded contents with every line ending normalized to `\n`.
+        pub content: String,
+        pub encoding: Encoding,
+        pub line_ending: LineEnding,
+        /// True when the file mixed several line ending styles. Writing it back
+        /// unifies them on `line_ending`, which is reported as a warning.
+        pub mixed_line_endings: bool,
+        /// EditorConfig rules applied when the file is written.
+        pub editorconfig: EditorConfig,
+        /// Format changes forced by the EditorConfig rules.
+        pub conversions: Vec<String>,
+    }
+
+    impl TextFile {
+        /// Read and decode a file, refusing anything that is not UTF-8 or UTF-16
+        /// since rewriting it would silently change its encoding.
+        pub fn read(path: &Path) -> Result<Self, Box<dyn Error>> {
+            let bytes = fs::read(path)?;
+            let mut file =
+                Self::from_bytes(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?;
+            file.apply_editorconfig(editorconfig::resolve(path));
+            Ok(file)
+        }
+
+        /// Format for a file that does not exist yet (or whose contents could
+        /// not be decoded): UTF-8 with the line endings `content` already
+        /// uses, adjusted by any EditorConfig rules for `path`.
+        pub fn for_new_file(path: &Path, content: &str) -> Self {
+            let (line_ending, _) = detect_line_ending(content);
+            let mut file = Self {
+                line_ending,
+                ..Self::new_utf8("")
+            };
+            file.apply_editorconfig(editorconfig::resolve(path));
+            file
+        }
+
+        /// Decode raw bytes and detect encoding and line endings.
+        pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+            let (raw, encoding) = decode(bytes)?;
+            let (line_ending, mixed_line_endings) = detect_line_ending(&raw);
+            Ok(Self {
+                content: normalize_line_endings(&raw),
+                encoding,
+                line_ending,
+                mixed_line_endings,
+                editorconfig: EditorConfig::default(),
+                conversions: Vec::new(),
+            })
+        }
+
+        /// Format used for brand-new files: plain UTF-8 with LF endings.
+        pub fn new_utf8(content: &str) -> Self {
+            Self {
+                content: normalize_line_endings(content),
+                encoding: Encoding::Utf8 { bom: false },
+                line_ending: LineEnding::Lf,
+                mixed_line_endings: false,
+                editorconfig: EditorConfig::default(),
+                conversions: Vec::new(),
+            }
+        }
+
+        /// Switch the output format to what `config` requires, recording each
+        /// change so it can be reported.
+        pub fn apply_editorconfig(&mut self, config: EditorConfig) {
+            let source = config_source(&config);
+            let line_ending = match config.end_of_line.as_deref() {
+                Some("lf") => Some(LineEnding::Lf),
+                Some("crlf") => Some(LineEnding::Crlf),
+                Some("cr") => Some(LineEnding::Cr),
+                _ => None,
+            };
+            if let Some(required) = line_ending
+                && required != self.line_ending
+            {
+                self.conversions.push(format!(
+                    "line endings converted from {} to {} (end_of_line in {})",
+                    self.line_ending.label(),
+                    required.label(),
+                    source
+                ));
+                self.line_ending = required;
+                // Unifying mixed endings is part of the conversion
+                self.mixed_line_endings = false;
+            }
+
+            let encoding = match config.charset.as_deref() {
+                Some("utf-8") => Some(Encoding::Utf8 { bom: false }),
+                Some("utf-8-bom") => Some(Encoding::Utf8 { bom: true }),
+                Some("utf-16le") => Some(Encoding::Utf16Le { bom: true }),
+                Some("utf-16be") => Some(Encoding::Utf16Be { bom: true }),
+                _ => None,
+            };
+            if let Some(required) = encoding
+                && required != self.encoding
+            {
+                self.conversions.push(format!(
+                    "encoding converted from {} to {} (charset in {})",
+                    self.encoding.label(),
+                    required.label(),
+                    source
+                ));
+                self.encoding = required;
+            }
+            self.editorconfig = config;
+        }
+
+        /// Apply the content rules of the EditorConfig (final newline) and
+        /// describe indentation that breaks `indent_style`. Only violations
+        /// introduced relative to the current content are reported, so
+        /// untouched legacy lines do not produce noise.
+        pub fn enforce(&self, content: &str) -> (String, Vec<String>) {
+            let config = &self.editorconfig;
+            let source = config_source(config);
+            let mut content = normalize_line_endings(content);
+            let mut warnings = Vec::new();
+
+            match config.insert_final_newline {
+                Some(true) if !content.is_empty() && !content.ends_with('\n') => {
+                    content.push('\n');
+                    warnings.push(format!(
+                        "added the final newline required by insert_final_newline in {}",
+                        source
+                    ));
+                }
+                Some(false) if content.ends_with('\n') => {
+                    // Only the final line terminator; blank lines before it stay
+                    content.pop();
+                    warnings.push(format!(
+                        "removed the final newline (insert_final_newline = false in {})",
+                        source
+                    ));
+                }
+                _ => {}
+            }
+
+            if config.charset.as_deref() == Some("latin1") && !content.is_ascii() {
+                warnings.push(format!(
+                    "charset in {} is latin1, but the file was written as {} because it contains non-ASCII text",
+                    source,
+                    self.encoding.label()
+                ));
+            }
+
+            if let Some(style) = config.indent_style {
+                let width = config.indent_width().unwrap_or(4);
+                let before = indent_violations(&self.content, style, width);
+                let after = indent_violations(&content, style, width);
+                // Lines are compared by text since edits shift line numbers
+                let introduced: Vec<usize> = after
+                    .iter()
+                    .filter(|(_, line)| !before.iter().any(|(_, old)| old == line))
+                    .map(|(number, _)| *number)
+                    .collect();
+                if !introduced.is_empty() {
+                    let (expected, found) = match style {
+                        IndentStyle::Tab => ("tabs", "spaces"),
+                        IndentStyle::Space => ("spaces", "tabs"),
+                    };
+                    let examples: Vec<String> =
+                        introduced.iter().take(5).map(|n| n.to_string()).collect();
+                    warnings.push(format!(
+                        "{} line(s) are indented with {} but indent_style in {} requires {} (lines {})",
+                        introduced.len(),
+                        found,
+                        source,
+                        expected,
+                        examples.join(", ")
+                    ));
+                }
+            }
+
+            (content, warnings)
+        }
+
+        /// Encode `content` (which may use any line endings) with this file's
+        /// encoding and line ending style.
+        pub fn encode(&self, content: &str) -> Vec<u8> {
+            let normalized = normalize_line_endings(content);
+            encode(
+                &apply_line_ending(&normalized, self.line_ending),
+                self.encoding,
+            )
+        }
+
+        /// Write `content` to `path` using this file's encoding and line endings.
+        /// Returns warnings describing anything that could not be preserved.
+        pub fn write(&self, path: &Path, content: &str) -> Result<Vec<String>, Box<dyn Error>> {
+            let (content, enforced) = self.enforce(content);
+            file_write::write_preserving(path, &self.encode(&content))?;
+            let mut warnings = self.warnings();
+            warnings.extend(enforced);
+            Ok(warnings)
+        }
+
+        /// Formatting changes that writing this file back will introduce.
+        pub fn warnings(&self) -> Vec<String> {
+            let mut warnings = Vec::new();
+            if self.mixed_line_endings {
+                warnings.push(format!(
+                    "file had mixed line endings; they were unified to {}",
+                    self.line_ending.label()
+                ));
+            }
+            warnings.extend(self.conversions.iter().cloned());
+            warnings
+        }
+
+        /// Describe the preserved format when it differs from plain UTF-8/LF, so
+        /// callers can mention it in their result message.
+        pub fn format_note(&self) -> Option<String> {
+            // Converted formats are described by the conversion warnings
+            if !self.conversions.is_empty() {
+                return None;
+            }
+            if self.encoding == (Encoding::Utf8 { bom: false })
+                && self.line_ending == LineEnding::Lf
+            {
+                return None;
+            }
+            Some(format!(
+                "preserved {} / {}",
+                self.encoding.label(),
+                self.line_ending.label()
+            ))
+        }
+    }
+
+    /// Append the format note and warnings to a tool result message.
+    pub fn annotate(message: String, file: &TextFile, warnings: &[String]) -> String {
+        let mut out = message;
+        if let Some(note) = file.format_note() {
+            out.push_str(&format!(" ({})", note));
+        }
+        for warning in warnings {
+            out.push_str(&format!("\nWarning: {}", warning));
+        }
+        out
+    }
+
+    fn config_source(config: &EditorConfig) -> String {
+        config
+            .source
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| ".editorconfig".to_string())
+    }
+
+    /// 1-based numbers of lines whose leading whitespace uses the wrong
+    /// indentation character. With tabs, a run of spaces shorter than one
+    /// indent level is treated as alignment.
+    fn indent_violations(content: &str, style: IndentStyle, width: usize) -> Vec<(usize, &str)> {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                let body = line.trim_start_matches([' ', '\t']);
+                if body.is_empty() {
+                    return false;
+                }
+                let leading = &line[..line.len() - body.len()];
+                match style {
+                    IndentStyle::Tab => leading.starts_with(&" ".repeat(width.max(1))),
+                    IndentStyle::Space => leading.contains('\t'),
+                }
+            })
+            .map(|(i, line)| (i + 1, line))
+            .collect()
+    }
+
+    /// Decode bytes as UTF-8 or UTF-16, using a BOM when present and a NUL-byte
+    /// heuristic for BOM-less UTF-16.
+    pub fn decode(bytes: &[u8]) -> Result<(String, Encoding), Box<dyn Error>> {
+        if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+            let text = String::from_utf8(rest.to_vec())
+                .map_err(|_| "file has a UTF-8 BOM but is not valid UTF-8")?;
+            return Ok((text, Encoding::Utf8 { bom: true }));
+        }
+        if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+            return Ok((decode_utf16(rest, true)?, Encoding::Utf16Le { bom: true }));
+        }
+        if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+            return Ok((decode_utf16(rest, false)?, Encoding::Utf16Be { bom: true }));
+        }
+
+        if let Some(little_endian) = guess_bomless_utf16(bytes) {
+            let text = decode_utf16(bytes, little_endian)?;
+            let encoding = if little_endian {
+                Encoding::Utf16Le { bom: false }
+            } else {
+                Encoding::Utf16Be { bom: false }
+            };
+            return Ok((text, encoding));
+        }
+
+        match String::from_utf8(bytes.to_vec()) {
+            Ok(text) => Ok((text, Encoding::Utf8 { bom: false })),
+            Err(_) => Err("file is not valid UTF-8 or UTF-16; refusing to rewrite it because its encoding cannot be preserved".into()),
+        }
+    }
+
+    fn decode_utf16(bytes: &[u8], little_endian: bool) -> Result<String, Box<dyn Error>> {
+        if !bytes.len().is_multiple_of(2) {
+            return Err("file looks like UTF-16 but has an odd number of bytes".into());
+        }
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| {
+                if little_endian {
+                    u16::from_le_bytes([pair[0], pair[1]])
+                } else {
+                    u16::from_be_bytes([pair[0], pair[1]])
+                }
+            })
+            .collect();
+        String::from_utf16(&units).map_err(|_| "file contains invalid UTF-16".into())
+    }
+
+    /// BOM-less UTF-16 text in mostly-ASCII files has a NUL in every other byte.
+    /// Returns `Some(true)` for little endian, `Some(false)` for big endian.
+    fn guess_bomless_utf16(bytes: &[u8]) -> Option<bool> {
+        if bytes.len() < 4 || !bytes.len().is_multiple_of(2) {
+            return None;
+        }
+        let pairs = bytes.len() / 2;
+        let even_nuls = bytes.iter().step_by(2).filter(|b| **b == 0).count();
+        let odd_nuls = bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+        if odd_nuls * 10 >= pairs * 9 && even_nuls == 0 {
+            Some(true)
+        } else if even_nuls * 10 >= pairs * 9 && odd_nuls == 0 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Encode text with the given encoding, emitting a BOM when it had one.
+    pub fn encode(text: &str, encoding: Encoding) -> Vec<u8> {
+        match encoding {
+            Encoding::Utf8 { bom } => {
+                let mut out = Vec::with_capacity(text.len() + 3);
+                if bom {
+                    out.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+                }
+                out.extend_from_slice(text.as_bytes());
+                out
+            }
+            Encoding::Utf16Le { bom } => {
+                let mut out = Vec::with_capacity(text.len() * 2 + 2);
+                if bom {
+                    out.extend_from_slice(&[0xFF, 0xFE]);
+                }
+                for unit in text.encode_utf16() {
+                    out.extend_from_slice(&unit.to_le_bytes());
+                }
+                out
+            }
+            Encoding::Utf16Be { bom } => {
+                let mut out = Vec::with_capacity(text.len() * 2 + 2);
+                if bom {
+                    out.extend_from_slice(&[0xFE, 0xFF]);
+                }
+                for unit in text.encode_utf16() {
+                    out.extend_from_slice(&unit.to_be_bytes());
+                }
+                out
+            }
+        }
+    }
+
+    /// Return the dominant line ending and whether more than one style is used.
+    /// Files without any line break default to LF.
+    pub fn detect_line_ending(text: &str) -> (LineEnding, bool) {
+        let (mut crlf, mut lf, mut cr) = (0usize, 0usize, 0usize);
+        let bytes = text.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                    crlf += 1;
+                    i += 1;
+                }
+                b'\r' => cr += 1,
+                b'\n' => lf += 1,
+                _ => {}
+            }
+            i += 1;
+        }
+
+        let kinds = [crlf, lf, cr].iter().filter(|c| **c > 0).count();
+        let ending = if crlf >= lf && crlf >= cr && crlf > 0 {
+            LineEnding::Crlf
+        } else if cr > lf {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        };
+        (ending, kinds > 1)
+    }
+
+    /// Convert CRLF and lone CR line endings to LF.
+    pub fn normalize_line_endings(text: &str) -> String {
+        text.replace("\r\n", "\n").replace('\r', "\n")
+    }
+
+    /// Convert LF-normalized text to the requested line ending.
+    pub fn apply_line_ending(text: &str, ending: LineEnding) -> String {
+        match ending {
+            LineEnding::Lf => text.to_string(),
+            other => text.replace('\n', other.as_str()),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_crlf_round_trip() {
+            let file = TextFile::from_bytes(b"one\r\ntwo\r\n").unwrap();
+            assert_eq!(file.content, "one\ntwo\n");
+            assert_eq!(file.line_ending, LineEnding::Crlf);
+            assert!(!file.mixed_line_endings);
+            assert_eq!(file.encode("one\nthree\n"), b"one\r\nthree\r\n");
+        }
+
+        #[test]
+        fn test_utf8_bom_round_trip() {
+            let file = TextFile::from_bytes(b"\xEF\xBB\xBFhello\n").unwrap();
+            assert_eq!(file.encoding, Encoding::Utf8 { bom: true });
+            assert_eq!(file.content, "hello\n");
+            assert_eq!(file.encode("bye\n"), b"\xEF\xBB\xBFbye\n");
+        }
+
+        #[test]
+        fn test_utf16_le_with_bom_round_trip() {
+            let mut bytes = vec![0xFF, 0xFE];
+            for unit in "a\r\nb".encode_utf16() {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+            let file = TextFile::from_bytes(&bytes).unwrap();
+            assert_eq!(file.encoding, Encoding::Utf16Le { bom: true });
+            assert_eq!(file.line_ending, LineEnding::Crlf);
+            assert_eq!(file.content, "a\nb");
+            assert_eq!(file.encode("a\nb"), bytes);
+        }
+
+        #[test]
+        fn test_bomless_utf16_be_detected() {
+            let mut bytes = Vec::new();
+            for unit in "text\n".encode_utf16() {
+                bytes.extend_from_slice(&unit.to_be_bytes());
+            }
+            let file = TextFile::from_bytes(&bytes).unwrap();
+            assert_eq!(file.encoding, Encoding::Utf16Be { bom: false });
+            assert_eq!(file.content, "text\n");
+        }
+
+        #[test]
+        fn test_mixed_line_endings_warn() {
+            let file = TextFile::from_bytes(b"a\r\nb\r\nc\n").unwrap();
+            assert_eq!(file.line_ending, LineEnding::Crlf);
+            assert!(file.mixed_line_endings);
+            assert_eq!(file.warnings().len(), 1);
+        }
+
+        #[test]
+        fn test_editorconfig_rules_are_enforced_on_write() {
+            let dir = tempfile::TempDir::new().unwrap();
+            fs::write(
+                dir.path().join(".editorconfig"),
+                "root = true\n[*]\nend_of_line = lf\ninsert_final_newline = true\nindent_style = tab\n",
+            )
+            .unwrap();
+            let path = dir.path().join("main.go");
+            fs::write(&path, "func a() {\r\n\treturn\r\n}\r\n").unwrap();
+
+            let file = TextFile::read(&path).unwrap();
+            assert_eq!(file.line_ending, LineEnding::Lf);
+            let warnings = file
+                .write(&path, "func a() {\n\treturn\n    b()\n}")
+                .unwrap();
+            assert_eq!(
+                fs::read_to_string(&path).unwrap(),
+                "func a() {\n\treturn\n    b()\n}\n"
+            );
+            // CRLF conversion, final newline and the space-indented line
+            assert_eq!(warnings.len(), 3, "{:?}", warnings);
+            assert!(warnings[2].contains("lines 3"));
+        }
+
+        #[test]
+        fn test_final_newline_removal_keeps_blank_lines() {
+            let dir = tempfile::TempDir::new().unwrap();
+            fs::write(
+                dir.path().join(".editorconfig"),
+                "root = true\n[*]\ninsert_final_newline = false\n",
+            )
+            .unwrap();
+            let path = dir.path().join("notes.txt");
+            fs::write(&path, "a\r\nb\r\n").unwrap();
+
+            let file = TextFile::read(&path).unwrap();
+            let warnings = file.write(&path, "a\nb\n\n\n").unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "a\r\nb\r\n\r\n");
+            assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        }
+
+        #[test]
+        fn test_invalid_encoding_is_rejected() {
+            let result = TextFile::from_bytes(&[0x63, 0x61, 0x66, 0xE9, 0x0A]);
+            assert!(result.is_err());
+        }
+    }
+}
@@ -0,0 +1,514 @@
+pub mod trash {
+    //! Per-session trash for files removed or rewritten by tools.
+    //!
+    //! Entries live under `<workspace>/.pengy/trash/<session>/<entry-id>/` as a
+    //! `meta.json` describing the original path plus a `payload` holding the
+    //! file or directory contents. `delete_file` moves paths here, file_manager
+    //! snapshots files before overwriting them, and `restore_file` (or the TUI
+    //! `/trash` browser) puts them back.
+    //!
+    //! The trash is bounded: paths larger than [`MAX_ENTRY_BYTES`] are not
+    //! kept (deleting them is permanent and callers say so), and after every
+    //! new entry those older than [`MAX_AGE`] or beyond [`MAX_TRASH_BYTES`] in
+    //! total, oldest first, are pruned. Symlinks are stored as links, never
+    //! followed.
+
+    use serde::{Deserialize, Serialize};
+    use std::error::Error;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::RwLock;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const META_FILE: &str = "meta.json";
+    const PAYLOAD: &str = "payload";
+    /// Largest file or directory kept in the trash.
+    pub const MAX_ENTRY_BYTES: u64 = 100 * 1024 * 1024;
+    /// Total size the trash is pruned down to.
+    pub const MAX_TRASH_BYTES: u64 = 500 * 1024 * 1024;
+    /// Entries older than this are pruned.
+    pub const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+    static SESSION: RwLock<Option<String>> = RwLock::new(None);
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    static DEFAULT_SESSION: once_cell::sync::Lazy<String> =
+        once_cell::sync::Lazy::new(|| format!("run-{}", now_millis() / 1000));
+
+    /// Metadata describing one trashed file or directory.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct TrashEntry {
+        /// Unique id within the session, used to restore the entry.
+        pub id: String,
+        /// Session the entry belongs to.
+        pub session: String,
+        /// Absolute path the payload came from and will be restored to.
+        pub original_path: PathBuf,
+        /// Why the entry was created, e.g. "deleted" or "overwritten".
+        pub reason: String,
+        /// Creation time in milliseconds since the Unix epoch.
+        pub timestamp: u64,
+        pub is_dir: bool,
+        /// Size in bytes of the payload (the total of its files for
+        /// directories).
+        pub size: u64,
+    }
+
+    impl TrashEntry {
+        /// One-line description used in tool output and the TUI browser.
+        pub fn summary(&self) -> String {
+            format!(
+                "{} [{}] {}{}",
+                self.id,
+                self.reason,
+                self.original_path.display(),
+                if self.is_dir { "/" } else { "" }
+            )
+        }
+    }
+
+    /// Set the session new entries are recorded under (the TUI uses the
+    /// current chat session).
+    pub fn set_session(session: &str) {
+        if let Ok(mut slot) = SESSION.write() {
+            *slot = Some(sanitize(session));
+        }
+    }
+
+    /// Session new entries are recorded under.
+    pub fn current_session() -> String {
+        SESSION
+            .read()
+            .ok()
+            .and_then(|s| s.clone())
+            .unwrap_or_else(|| DEFAULT_SESSION.clone())
+    }
+
+    /// Root directory holding all sessions' trash for a workspace.
+    pub fn trash_root(workspace: &Path) -> PathBuf {
+        workspace.join(".pengy").join("trash")
+    }
+
+    /// Copy `path` into the trash, leaving the original in place. Used before
+    /// a file is overwritten or truncated. Returns `None` without copying
+    /// anything when `path` is larger than [`MAX_ENTRY_BYTES`].
+    pub fn snapshot(
+        workspace: &Path,
+        path: &Path,
+        reason: &str,
+    ) -> Result<Option<TrashEntry>, Box<dyn Error>> {
+        ensure_outside_trash(workspace, path)?;
+        let Some(size) = size_within(path, MAX_ENTRY_BYTES) else {
+            return Ok(None);
+        };
+        let (entry, entry_dir) = new_entry(workspace, path, reason, size)?;
+        let payload = entry_dir.join(PAYLOAD);
+        if let Err(e) = copy_recursive(path, &payload) {
+            let _ = fs::remove_dir_all(&entry_dir);
+            return Err(format!("could not snapshot {} to trash: {}", path.display(), e).into());
+        }
+        write_meta(&entry_dir, &entry)?;
+        prune(workspace);
+        Ok(Some(entry))
+    }
+
+    /// Tool-output note for the result of [`snapshot`].
+    pub fn snapshot_note(saved: Option<&TrashEntry>) -> String {
+        match saved {
+            Some(entry) => format!("original saved to trash as {}", entry.id),
+            None => format!(
+                "original not kept: larger than the {} MB trash limit",
+                MAX_ENTRY_BYTES / (1024 * 1024)
+            ),
+        }
+    }
+
+    /// Move `path` into the trash. Used instead of deleting it outright. A
+    /// path larger than [`MAX_ENTRY_BYTES`] is deleted permanently and `None`
+    /// is returned.
+    pub fn move_to_trash(
+        workspace: &Path,
+        path: &Path,
+        reason: &str,
+    ) -> Result<Option<TrashEntry>, Box<dyn Error>> {
+        let entry = move_unpruned(workspace, path, reason)?;
+        prune(workspace);
+        Ok(entry)
+    }
+
+    fn move_unpruned(
+        workspace: &Path,
+        path: &Path,
+        reason: &str,
+    ) -> Result<Option<TrashEntry>, Box<dyn Error>> {
+        ensure_outside_trash(workspace, path)?;
+        let Some(size) = size_within(path, MAX_ENTRY_BYTES) else {
+            remove_path(path)?;
+            return Ok(None);
+        };
+        let (entry, entry_dir) = new_entry(workspace, path, reason, size)?;
+        let payload = entry_dir.join(PAYLOAD);
+        if fs::rename(path, &payload).is_err() {
+            // Different filesystem: copy, then remove the original
+            let result = copy_recursive(path, &payload).and_then(|_| remove_path(path));
+            if let Err(e) = result {
+                let _ = fs::remove_dir_all(&entry_dir);
+                return Err(format!("could not move {} to trash: {}", path.display(), e).into());
+            }
+        }
+        write_meta(&entry_dir, &entry)?;
+        Ok(Some(entry))
+    }
+
+    /// Entries in the trash, newest first. With `session` set only that
+    /// session's entries are returned.
+    pub fn list(workspace: &Path, session: Option<&str>) -> Vec<TrashEntry> {
+        let root = trash_root(workspace);
+        let session_dirs: Vec<PathBuf> = match session {
+            Some(s) => vec![root.join(sanitize(s))],
+            None => fs::read_dir(&root)
+                .map(|rd| rd.flatten().map(|e| e.path()).collect())
+                .unwrap_or_default(),
+        };
+
+        let mut entries: Vec<TrashEntry> = session_dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flat_map(|rd| rd.flatten())
+            .filter_map(|e| fs::read_to_string(e.path().join(META_FILE)).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+        entries
+    }
+
+    /// Find an entry by id in any session.
+    pub fn find(workspace: &Path, id: &str) -> Option<TrashEntry> {
+        list(workspace, None).into_iter().find(|e| e.id == id)
+    }
+
+    /// Put an entry back at its original path and remove it from the trash.
+    /// Whatever currently occupies that path is snapshotted first, so a
+    /// restore can itself be undone.
+    pub fn restore(workspace: &Path, entry: &TrashEntry) -> Result<String, Box<dyn Error>> {
+        let entry_dir = trash_root(workspace).join(&entry.session).join(&entry.id);
+        let payload = entry_dir.join(PAYLOAD);
+        if !payload.exists() {
+            return Err(format!("Trash entry {} has no payload", entry.id).into());
+        }
+
+        let target = &entry.original_path;
+        let mut message = String::new();
+        if fs::symlink_metadata(target).is_ok() {
+            // Not pruned yet: that could remove the entry being restored
+            message = match move_unpruned(workspace, target, "replaced by restore")? {
+                Some(replaced) => {
+                    format!(" (previous contents moved to trash as {})", replaced.id)
+                }
+                None => {
+                    " (previous contents were too large for the trash and were deleted)".to_string()
+                }
+            };
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::rename(&payload, target).is_err() {
+            copy_recursive(&payload, target)?;
+        }
+        let _ = fs::remove_dir_all(&entry_dir);
+        prune(workspace);
+
+        Ok(format!("Restored {}{}", target.display(), message))
+    }
+
+    /// Refuse paths that contain the trash itself (`.pengy`, the workspace
+    /// root, ...): their payload would have to be written inside them.
+    /// Symlinks are moved as links and never contain anything.
+    fn ensure_outside_trash(workspace: &Path, path: &Path) -> Result<(), Box<dyn Error>> {
+        if fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Ok(());
+        }
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            workspace.join(path)
+        };
+        let Ok(canonical) = fs::canonicalize(&absolute) else {
+            return Ok(());
+        };
+        let root = trash_root(workspace);
+        let root = fs::canonicalize(&root)
+            .or_else(|_| fs::canonicalize(workspace).map(|w| trash_root(&w)))
+            .unwrap_or(root);
+        if root.starts_with(&canonical) {
+            return Err(format!(
+                "{} contains the trash ({}) and cannot be moved into it",
+                path.display(),
+                root.display()
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    fn new_entry(
+        workspace: &Path,
+        path: &Path,
+        reason: &str,
+        size: u64,
+    ) -> Result<(TrashEntry, PathBuf), Box<dyn Error>> {
+        let metadata = fs::symlink_metadata(path)?;
+        let session = current_session();
+        let timestamp = now_millis();
+        let id = format!("{}-{}", timestamp, COUNTER.fetch_add(1, Ordering::Relaxed));
+        let entry_dir = trash_root(workspace).join(&session).join(&id);
+        fs::create_dir_all(&entry_dir)?;
+
+        let original_path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            workspace.join(path)
+        };
+        let entry = TrashEntry {
+            id,
+            session,
+            original_path,
+            reason: reason.to_string(),
+            timestamp,
+            is_dir: metadata.is_dir(),
+            size,
+        };
+        Ok((entry, entry_dir))
+    }
+
+    fn write_meta(entry_dir: &Path, entry: &TrashEntry) -> Result<(), Box<dyn Error>> {
+        fs::write(
+            entry_dir.join(META_FILE),
+            serde_json::to_string_pretty(entry)?,
+        )?;
+        Ok(())
+    }
+
+    /// Remove entries older than [`MAX_AGE`], then the oldest ones until the
+    /// rest fit in [`MAX_TRASH_BYTES`]. Failures are ignored; pruning is
+    /// retried after the next entry.
+    fn prune(workspace: &Path) {
+        let root = trash_root(workspace);
+        let cutoff = now_millis().saturating_sub(MAX_AGE.as_millis() as u64);
+        let mut total = 0u64;
+        for entry in list(workspace, None) {
+            total = total.saturating_add(entry.size);
+            if entry.timestamp < cutoff || total > MAX_TRASH_BYTES {
+                let session_dir = root.join(&entry.session);
+                let _ = fs::remove_dir_all(session_dir.join(&entry.id));
+                // Drop the session directory once its last entry is gone
+                let _ = fs::remove_dir(&session_dir);
+            }
+        }
+    }
+
+    /// Total size of the files under `path` without following symlinks, or
+    /// `None` as soon as it exceeds `limit`.
+    fn size_within(path: &Path, limit: u64) -> Option<u64> {
+        fn walk(path: &Path, total: &mut u64, limit: u64) -> bool {
+            let Ok(metadata) = fs::symlink_metadata(path) else {
+                return true;
+            };
+            if metadata.is_dir() {
+                let Ok(entries) = fs::read_dir(path) else {
+                    return true;
+                };
+                entries.flatten().all(|e| walk(&e.path(), total, limit))
+            } else {
+                *total = total.saturating_add(metadata.len());
+                *total <= limit
+            }
+        }
+        let mut total = 0;
+        walk(path, &mut total, limit).then_some(total)
+    }
+
+    fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+        let metadata = fs::symlink_metadata(from)?;
+        if metadata.file_type().is_symlink() {
+            copy_link(from, to)
+        } else if metadata.is_dir() {
+            fs::create_dir_all(to)?;
+            for entry in fs::read_dir(from)? {
+                let entry = entry?;
+                copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+            }
+            Ok(())
+        } else {
+            // fs::copy also carries the permission bits over
+            fs::copy(from, to).map(|_| ())
+        }
+    }
+
+    #[cfg(unix)]
+    fn copy_link(from: &Path, to: &Path) -> std::io::Result<()> {
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)
+    }
+
+    #[cfg(windows)]
+    fn copy_link(from: &Path, to: &Path) -> std::io::Result<()> {
+        let link = fs::read_link(from)?;
+        if fs::metadata(from).is_ok_and(|m| m.is_dir()) {
+            std::os::windows::fs::symlink_dir(link, to)
+        } else {
+            std::os::windows::fs::symlink_file(link, to)
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn copy_link(from: &Path, to: &Path) -> std::io::Result<()> {
+        fs::copy(from, to).map(|_| ())
+    }
+
+    fn remove_path(path: &Path) -> std::io::Result<()> {
+        if fs::symlink_metadata(path)?.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+    }
+
+    fn sanitize(session: &str) -> String {
+        let cleaned: String = session
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if cleaned.is_empty() {
+            "default".to_string()
+        } else {
+            cleaned
+        }
+    }
+
+    fn now_millis() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_snapshot_and_restore_roundtrip() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let file = dir.path().join("notes.txt");
+            fs::write(&file, "original").unwrap();
+
+            let entry = snapshot(dir.path(), &file, "overwritten").unwrap().unwrap();
+            fs::write(&file, "changed").unwrap();
+
+            assert!(
+                list(dir.path(), Some(&entry.session))
+                    .iter()
+                    .any(|e| e.id == entry.id)
+            );
+            restore(dir.path(), &entry).unwrap();
+            assert_eq!(fs::read_to_string(&file).unwrap(), "original");
+
+            // The replaced contents are themselves recoverable
+            let replaced = list(dir.path(), None);
+            assert_eq!(replaced.len(), 1);
+            assert_eq!(replaced[0].reason, "replaced by restore");
+        }
+
+        #[test]
+        fn test_move_directory_to_trash() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let sub = dir.path().join("build");
+            fs::create_dir_all(sub.join("nested")).unwrap();
+            fs::write(sub.join("nested").join("a.txt"), "a").unwrap();
+
+            let entry = move_to_trash(dir.path(), &sub, "deleted").unwrap().unwrap();
+            assert!(!sub.exists());
+            assert!(entry.is_dir);
+
+            let found = find(dir.path(), &entry.id).unwrap();
+            restore(dir.path(), &found).unwrap();
+            assert_eq!(
+                fs::read_to_string(sub.join("nested").join("a.txt")).unwrap(),
+                "a"
+            );
+            assert!(list(dir.path(), None).is_empty());
+        }
+
+        #[test]
+        fn test_paths_containing_the_trash_are_refused() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let file = dir.path().join("notes.txt");
+            fs::write(&file, "original").unwrap();
+            snapshot(dir.path(), &file, "overwritten").unwrap().unwrap();
+
+            let pengy = dir.path().join(".pengy");
+            let err = move_to_trash(dir.path(), &pengy, "deleted").unwrap_err();
+            assert!(err.to_string().contains("contains the trash"), "{}", err);
+            assert!(move_to_trash(dir.path(), dir.path(), "deleted").is_err());
+            assert!(move_to_trash(dir.path(), Path::new(".pengy"), "deleted").is_err());
+            assert_eq!(list(dir.path(), None).len(), 1);
+            assert_eq!(fs::read_to_string(&file).unwrap(), "original");
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_symlinks_are_trashed_as_links() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let outside = tempfile::TempDir::new().unwrap();
+            fs::write(outside.path().join("big.bin"), "outside").unwrap();
+            let sub = dir.path().join("pkg");
+            fs::create_dir_all(&sub).unwrap();
+            std::os::unix::fs::symlink(outside.path(), sub.join("link")).unwrap();
+
+            let entry = snapshot(dir.path(), &sub, "overwritten").unwrap().unwrap();
+            let copied = trash_root(dir.path())
+                .join(&entry.session)
+                .join(&entry.id)
+                .join(PAYLOAD)
+                .join("link");
+            assert!(
+                fs::symlink_metadata(&copied)
+                    .unwrap()
+                    .file_type()
+                    .is_symlink()
+            );
+            assert_eq!(fs::read_link(&copied).unwrap(), outside.path());
+        }
+
+        #[test]
+        fn test_prune_drops_old_and_excess_entries() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let file = dir.path().join("a.txt");
+            fs::write(&file, "a").unwrap();
+            let old = snapshot(dir.path(), &file, "overwritten").unwrap().unwrap();
+            let kept = snapshot(dir.path(), &file, "overwritten").unwrap().unwrap();
+
+            // Age the first entry past the retention period
+            let mut aged = old.clone();
+            aged.timestamp = 0;
+            let old_dir = trash_root(dir.path()).join(&old.session).join(&old.id);
+            write_meta(&old_dir, &aged).unwrap();
+            // and make the second look like it fills the trash on its own
+            let mut huge = kept.clone();
+            huge.size = MAX_TRASH_BYTES;
+            let kept_dir = trash_root(dir.path()).join(&kept.session).join(&kept.id);
+            write_meta(&kept_dir, &huge).unwrap();
+
+            let newest = snapshot(dir.path(), &file, "overwritten").unwrap().unwrap();
+            let ids: Vec<String> = list(dir.path(), None).into_iter().map(|e| e.id).collect();
+            assert_eq!(ids, vec![newest.id]);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+pub mod workspace_files {
+    //! Enumerate the files of a project tree for tools that scan or rewrite
+    //! many files at once, skipping VCS metadata, build output and dependency
+    //! directories.
+
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Directory names that are never descended into.
+    pub const IGNORED_DIRS: &[&str] = &[
+        ".git",
+        ".pengy",
+        "target",
+        "node_modules",
+        ".idea",
+        ".vscode",
+        ".svn",
+        ".hg",
+        "dist",
+        "build",
+        "__pycache__",
+    ];
+
+    /// True if `path` names an ignored directory.
+    pub fn is_ignored(path: &Path) -> bool {
+        path.file_name()
+            .and_then(|s| s.to_str())
+            .map(|name| IGNORED_DIRS.contains(&name))
+            .unwrap_or(false)
+    }
+
+    /// Regular files under `root` no larger than `max_bytes`, sorted, stopping
+    /// after `max_files`. Symlinks are not followed.
+    pub fn collect_files(root: &Path, max_files: usize, max_bytes: u64) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut stack = vec![root.to_path_buf()];
+        while let Some(path) = stack.pop() {
+            if files.len() >= max_files {
+                break;
+            }
+            if path != root && is_ignored(&path) {
+                continue;
+            }
+            let Ok(meta) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if meta.is_dir() {
+                if let Ok(entries) = fs::read_dir(&path) {
+                    stack.extend(entries.flatten().map(|e| e.path()));
+                }
+            } else if meta.is_file() && meta.len() <= max_bytes {
+                files.push(path);
+            }
+        }
+        files.sort();
+        files
+    }
+
+    /// Match a file name against a simple glob supporting `*` and `?`.
+    pub fn matches_glob(name: &str, pattern: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        let pattern: Vec<char> = pattern.chars().collect();
+        let (mut n, mut p) = (0, 0);
+        let mut star: Option<(usize, usize)> = None;
+        while n < name.len() {
+            if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+                n += 1;
+                p += 1;
+            } else if p < pattern.len() && pattern[p] == '*' {
+                star = Some((p, n));
+                p += 1;
+            } else if let Some((star_p, star_n)) = star {
+                p = star_p + 1;
+                n = star_n + 1;
+                star = Some((star_p, star_n + 1));
+            } else {
+                return false;
+            }
+        }
+        pattern[p..].iter().all(|c| *c == '*')
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_matches_glob() {
+            assert!(matches_glob("main.rs", "*.rs"));
+            assert!(matches_glob("main.rs", "m??n.*"));
+            assert!(!matches_glob("main.rs", "*.py"));
+            assert!(matches_glob("a.test.ts", "*.test.*"));
+        }
+
+        #[test]
+        fn test_collect_files_skips_ignored_dirs() {
+            let dir = tempfile::TempDir::new().unwrap();
+            fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+            fs::create_dir_all(dir.path().join("src")).unwrap();
+            fs::write(dir.path().join("node_modules/pkg/index.js"), "x").unwrap();
+            fs::write(dir.path().join("src/lib.rs"), "x").unwrap();
+
+            let files = collect_files(dir.path(), 100, 1024);
+            assert_eq!(files, vec![dir.path().join("src/lib.rs")]);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::constants::{CONFIG_FILE, DEFAULT_BASE_URL, EMBED_LOGO};
+use crate::session_filter::{self, SessionMeta, SessionQuery};
 use crate::theme::{THEMES, Theme};
 use pengy_agent::agent::agent::agent::{Agent, AgentEvent};
 use pengy_agent::agent::chat_agent::chat_agent::create_chat_agent;
@@ -9,18 +10,26 @@ use pengy_agent::agent::issue_agent::issue_agent::create_issue_agent;
 use pengy_agent::agent::pengy_agent::pengy_agent::run_pengy_agent;
 use pengy_agent::agent::test_agent::test_agent::create_test_agent;
 use pengy_agent::model::model::model::Model;
+use pengy_agent::tool::approval::approval::{self, ApprovalRequest, SelectionRequest};
+use pengy_agent::util::diagnose::diagnose;
+use pengy_agent::util::trash::trash::{self, TrashEntry};
 use ratatui::widgets::{ListState, ScrollbarState};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
 use std::io::Write;
+use std::path::Path;
 use std::process::Command;
+use std::time::{Duration, Instant};
 use std::{env, error::Error, fs, fs::OpenOptions, path::PathBuf};
 use tokio::sync::mpsc;
 
 const SESSION_DIR: &str = ".pengy/pengy_sessions";
 const SESSION_FILE_PREFIX: &str = "session_";
 const MAX_TITLE_LEN: usize = 64;
+const BRANCH_POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// Longest clipboard text `/paste` attaches; the rest is cut off.
+const MAX_PASTE_CHARS: usize = 100_000;
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum AppState {
@@ -32,6 +41,7 @@ pub enum AppState {
     CustomModel,
     AgentSelector,
     SessionSelector,
+    TrashBrowser,
     BaseUrlSelector,
     ThemeSelector,
     #[allow(dead_code)] // Editor disabled for performance - code kept for future use
@@ -85,6 +95,16 @@ pub struct Config {
     pub api_key: String,
     pub selected_model: Option<ModelOption>,
     pub theme_index: Option<usize>,
+    /// Read a one-sentence summary of a final response aloud (`/speak`) when
+    /// the terminal has lost focus or the run was slow.
+    #[serde(default)]
+    pub speak_summaries: bool,
+    /// Speech command to use instead of the platform default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tts_command: Option<String>,
+    /// Skip the startup check for new releases and its status bar notice.
+    #[serde(default)]
+    pub disable_update_notice: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -93,10 +113,20 @@ struct PersistedMessage {
     content: String,
 }
 
+/// A saved session. Listing sessions reads it with `M = IgnoredAny`, which
+/// skips the message contents.
 #[derive(Serialize, Deserialize)]
-struct PersistedSession {
+struct PersistedSession<M = PersistedMessage> {
     title: String,
-    messages: Vec<PersistedMessage>,
+    messages: Vec<M>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    workspace: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    created: Option<u64>,
 }
 
 pub struct App {
@@ -121,6 +151,16 @@ pub struct App {
     pub(crate) theme_list_state: ListState,
     pub(crate) sessions: Vec<String>,
     pub(crate) session_paths: Vec<PathBuf>,
+    pub(crate) session_meta: Vec<SessionMeta>,
+    pub(crate) session_filter: String,
+    pub(crate) session_filter_focused: bool,
+    /// Branch checked out when last polled; `None` outside a git repository.
+    pub(crate) observed_branch: Option<String>,
+    last_branch_check: Instant,
+    /// In-flight branch poll started by `check_branch_switch`.
+    branch_poll: Option<std::sync::mpsc::Receiver<Option<String>>>,
+    /// Earlier session for the current branch, resumable with `/resume`.
+    pub(crate) branch_session_offer: Option<usize>,
     pub(crate) current_session: usize,
     pub(crate) settings_api_key: String,
     pub(crate) settings_base_url: String,
@@ -149,6 +189,143 @@ pub struct App {
     pub(crate) modified_files: HashMap<String, (usize, usize)>,
     pub(crate) pending_tool_calls: Vec<PendingToolCall>,
     pub(crate) editor_state: crate::editor::editor::EditorState,
+    pub(crate) approval_rx: mpsc::UnboundedReceiver<PendingApproval>,
+    pub(crate) pending_approval: Option<PendingApproval>,
+    pub(crate) selection_rx: mpsc::UnboundedReceiver<PendingSelection>,
+    pub(crate) pending_selection: Option<PendingSelection>,
+    pub(crate) trash_entries: Vec<TrashEntry>,
+    pub(crate) trash_list_state: ListState,
+    pub(crate) trash_show_all: bool,
+    pub(crate) pending_context: Vec<ContextBlock>,
+    pub(crate) speak_summaries: bool,
+    pub(crate) tts_command: Option<String>,
+    pub(crate) speaker: crate::speech::Speaker,
+    /// Cleared by crossterm `FocusLost`, set again by `FocusGained`.
+    pub(crate) terminal_focused: bool,
+    /// When the current run was sent, for the `/speak` duration threshold.
+    run_started: Option<Instant>,
+    pub(crate) disable_update_notice: bool,
+    update_check: Option<std::sync::mpsc::Receiver<Option<String>>>,
+    /// Newer release found by the startup check, shown in the status bar.
+    pub(crate) available_update: Option<String>,
+    /// `/diagnose` prompt being built on a worker thread.
+    diagnosis: Option<std::sync::mpsc::Receiver<Result<String, String>>>,
+    /// `/paste` label and the clipboard read in flight for it.
+    paste: Option<(String, std::sync::mpsc::Receiver<Result<String, String>>)>,
+    #[cfg(feature = "voice")]
+    pub(crate) voice: crate::voice::VoiceInput,
+}
+
+/// Text attached with `/paste`, sent ahead of the user's next message.
+#[derive(Clone)]
+pub(crate) struct ContextBlock {
+    pub label: String,
+    pub content: String,
+}
+
+impl ContextBlock {
+    /// Render as a labeled fenced block. The fence is longer than any run of
+    /// backticks in the content so pasted markdown cannot close it early.
+    pub(crate) fn render(&self) -> String {
+        let mut longest = 0;
+        let mut run = 0;
+        for c in self.content.chars() {
+            run = if c == '`' { run + 1 } else { 0 };
+            longest = longest.max(run);
+        }
+        let fence = "`".repeat((longest + 1).max(3));
+        format!(
+            "[Pasted context: {}]\n{}\n{}\n{}",
+            self.label, fence, self.content, fence
+        )
+    }
+}
+
+/// A tool action waiting for the user to approve or deny it, together with
+/// the channel the blocked tool is listening on for the answer.
+pub(crate) struct PendingApproval {
+    pub request: ApprovalRequest,
+    pub respond: std::sync::mpsc::Sender<bool>,
+}
+
+/// Rows of the selection picker: a group heading covering `start..end` of
+/// the items, or a single item.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum SelectionRow {
+    Group { start: usize, end: usize },
+    Item(usize),
+}
+
+/// Items a tool asked the user to include or exclude, with the picker state.
+pub(crate) struct PendingSelection {
+    pub request: SelectionRequest,
+    pub accepted: Vec<bool>,
+    pub rows: Vec<SelectionRow>,
+    pub list_state: ListState,
+    pub respond: std::sync::mpsc::Sender<Option<Vec<bool>>>,
+}
+
+impl PendingSelection {
+    fn new(request: SelectionRequest, respond: std::sync::mpsc::Sender<Option<Vec<bool>>>) -> Self {
+        let mut rows = Vec::new();
+        let mut start = 0;
+        while start < request.items.len() {
+            let group = &request.items[start].group;
+            let end = request.items[start..]
+                .iter()
+                .position(|item| &item.group != group)
+                .map(|offset| start + offset)
+                .unwrap_or(request.items.len());
+            rows.push(SelectionRow::Group { start, end });
+            rows.extend((start..end).map(SelectionRow::Item));
+            start = end;
+        }
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        Self {
+            accepted: vec![true; request.items.len()],
+            request,
+            rows,
+            list_state,
+            respond,
+        }
+    }
+
+    pub(crate) fn move_cursor(&mut self, delta: isize) {
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let last = self.rows.len().saturating_sub(1) as isize;
+        self.list_state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    /// Toggle the highlighted hunk, or every hunk of the highlighted group.
+    pub(crate) fn toggle_current(&mut self) {
+        match self
+            .list_state
+            .selected()
+            .and_then(|i| self.rows.get(i).copied())
+        {
+            Some(SelectionRow::Item(i)) => self.accepted[i] = !self.accepted[i],
+            Some(SelectionRow::Group { start, end }) => {
+                let value = !self.accepted[start..end].iter().all(|a| *a);
+                self.accepted[start..end].fill(value);
+            }
+            None => {}
+        }
+    }
+
+    pub(crate) fn toggle_all(&mut self) {
+        let value = !self.accepted.iter().all(|a| *a);
+        self.accepted.fill(value);
+    }
+
+    /// Item whose preview should be shown for the highlighted row.
+    pub(crate) fn current_item(&self) -> Option<usize> {
+        match self.rows.get(self.list_state.selected()?)? {
+            SelectionRow::Item(i) => Some(*i),
+            SelectionRow::Group { start, .. } => Some(*start),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -188,7 +365,7 @@ impl App {
             .collect()
     }
 
-    fn session_dir() -> PathBuf {
+    pub(crate) fn session_dir() -> PathBuf {
         std::env::current_dir()
             .unwrap_or_else(|_| std::path::PathBuf::from("."))
             .join(SESSION_DIR)
@@ -270,26 +447,73 @@ impl App {
             .collect()
     }
 
-    fn write_session_file(path: &PathBuf, title: &str, messages: &[ChatMessage]) {
+    fn write_session_file(
+        path: &PathBuf,
+        title: &str,
+        messages: &[ChatMessage],
+        meta: &SessionMeta,
+    ) {
         let dir = Self::ensure_session_dir();
         let _ = fs::create_dir_all(&dir);
         let data = PersistedSession {
             title: title.to_string(),
             messages: Self::chat_to_persist(messages),
+            tags: meta.tags.clone(),
+            workspace: Some(meta.workspace.clone()),
+            branch: meta.branch.clone(),
+            created: Some(meta.created),
         };
         if let Ok(json) = serde_json::to_string_pretty(&data) {
             let _ = fs::write(path, json);
         }
     }
 
-    fn read_session_file(path: &PathBuf) -> Option<(String, Vec<ChatMessage>)> {
+    pub(crate) fn read_session_file(
+        path: &PathBuf,
+    ) -> Option<(String, Vec<ChatMessage>, SessionMeta)> {
         let content = fs::read_to_string(path).ok()?;
         let parsed: PersistedSession = serde_json::from_str(&content).ok()?;
         let chat = Self::persist_to_chat(&parsed.messages);
-        Some((parsed.title, chat))
+        let (title, meta) = Self::session_meta(path, parsed);
+        Some((title, chat, meta))
     }
 
-    fn load_sessions_from_disk() -> (Vec<String>, Vec<PathBuf>) {
+    /// Title and metadata of a saved session, without its messages.
+    fn read_session_meta(path: &PathBuf) -> Option<(String, SessionMeta)> {
+        let content = fs::read_to_string(path).ok()?;
+        let parsed: PersistedSession<serde::de::IgnoredAny> =
+            serde_json::from_str(&content).ok()?;
+        Some(Self::session_meta(path, parsed))
+    }
+
+    fn session_meta<M>(path: &Path, parsed: PersistedSession<M>) -> (String, SessionMeta) {
+        // Sessions saved before metadata was recorded: the creation time is
+        // in the file name and the workspace is the one they are stored in.
+        let created = parsed.created.unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.strip_prefix(SESSION_FILE_PREFIX))
+                .and_then(|s| s.split('_').next())
+                .and_then(|ts| ts.parse().ok())
+                .unwrap_or(0)
+        });
+        let meta = SessionMeta {
+            tags: parsed.tags,
+            workspace: parsed
+                .workspace
+                .unwrap_or_else(|| Self::workspace_dir().display().to_string()),
+            branch: parsed.branch,
+            created,
+            messages: parsed.messages.len(),
+        };
+        (parsed.title, meta)
+    }
+
+    fn workspace_dir() -> PathBuf {
+        std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
+    }
+
+    fn load_sessions_from_disk() -> (Vec<String>, Vec<PathBuf>, Vec<SessionMeta>) {
         let dir = Self::ensure_session_dir();
         let mut entries: Vec<(std::time::SystemTime, PathBuf)> = Vec::new();
         if let Ok(read_dir) = fs::read_dir(&dir) {
@@ -313,35 +537,43 @@ impl App {
 
         let mut titles = Vec::new();
         let mut paths = Vec::new();
+        let mut metas = Vec::new();
 
         for (_, path) in entries.iter() {
-            if let Some((title, _messages)) = Self::read_session_file(&path.to_path_buf()) {
+            if let Some((title, meta)) = Self::read_session_meta(path) {
                 titles.push(title);
                 paths.push(path.to_path_buf());
+                metas.push(meta);
             }
         }
 
-        (titles, paths)
+        (titles, paths, metas)
     }
 
     pub(crate) fn save_current_session(&mut self) {
-        if let (Some(title), Some(path)) = (
+        if let (Some(title), Some(path), Some(meta)) = (
             self.sessions.get(self.current_session),
             self.session_paths.get(self.current_session),
+            self.session_meta.get_mut(self.current_session),
         ) {
-            Self::write_session_file(path, title, &self.chat_messages);
+            meta.messages = self.chat_messages.len();
+            Self::write_session_file(path, title, &self.chat_messages, meta);
             self.session_dirty = false;
         }
     }
 
     pub(crate) fn load_session(&mut self, idx: usize) {
         if let Some(path) = self.session_paths.get(idx).cloned() {
-            if let Some((title, messages)) = Self::read_session_file(&path) {
+            if let Some((title, messages, meta)) = Self::read_session_file(&path) {
                 if let Some(slot) = self.sessions.get_mut(idx) {
                     *slot = title;
                 }
+                if let Some(slot) = self.session_meta.get_mut(idx) {
+                    *slot = meta;
+                }
                 self.chat_messages = messages;
                 self.current_session = idx;
+                self.sync_trash_session();
                 self.session_list_state.select(Some(idx));
                 self.list_state.select(None);
                 self.user_scrolled = false;
@@ -351,6 +583,206 @@ impl App {
         }
     }
 
+    /// Id of the current session as used for its trash directory.
+    fn current_session_id(&self) -> Option<String> {
+        self.session_paths
+            .get(self.current_session)
+            .and_then(|p| p.file_stem())
+            .map(|s| s.to_string_lossy().to_string())
+    }
+
+    /// Record files removed or overwritten by tools under the current session.
+    fn sync_trash_session(&self) {
+        if let Some(id) = self.current_session_id() {
+            trash::set_session(&id);
+        }
+    }
+
+    pub(crate) fn refresh_trash_entries(&mut self) {
+        let workspace = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let session = if self.trash_show_all {
+            None
+        } else {
+            Some(trash::current_session())
+        };
+        self.trash_entries = trash::list(&workspace, session.as_deref());
+        let selected = self
+            .trash_list_state
+            .selected()
+            .unwrap_or(0)
+            .min(self.trash_entries.len().saturating_sub(1));
+        self.trash_list_state
+            .select((!self.trash_entries.is_empty()).then_some(selected));
+    }
+
+    /// Restore the trash entry highlighted in the `/trash` browser.
+    pub(crate) fn restore_selected_trash_entry(&mut self) {
+        let Some(entry) = self
+            .trash_list_state
+            .selected()
+            .and_then(|i| self.trash_entries.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let workspace = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        match trash::restore(&workspace, &entry) {
+            Ok(msg) => self.chat_messages.push(ChatMessage::Assistant(msg)),
+            Err(err) => self
+                .chat_messages
+                .push(ChatMessage::Error(format!("[trash] {}", err))),
+        }
+        self.session_dirty = true;
+        self.save_current_session();
+        self.refresh_trash_entries();
+    }
+
+    /// Indices of sessions matching the selector's filter query.
+    pub(crate) fn filtered_sessions(&self) -> Vec<usize> {
+        let query = SessionQuery::parse(&self.session_filter);
+        self.sessions
+            .iter()
+            .enumerate()
+            .filter(|(i, title)| {
+                self.session_meta
+                    .get(*i)
+                    .is_none_or(|meta| query.matches(title, meta))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Point the selector at the current session within the filtered list,
+    /// or at the first match if it is filtered out.
+    pub(crate) fn select_current_in_session_filter(&mut self) {
+        let filtered = self.filtered_sessions();
+        let position = filtered
+            .iter()
+            .position(|&i| i == self.current_session)
+            .unwrap_or(0);
+        self.session_list_state
+            .select((!filtered.is_empty()).then_some(position));
+    }
+
+    /// Handle `/tag`: add tags, remove `-tag`s, or list the current tags.
+    pub(crate) fn tag_current_session(&mut self, args: &str) -> Result<String, String> {
+        let Some(meta) = self.session_meta.get_mut(self.current_session) else {
+            return Err("no active session".to_string());
+        };
+        let mut changed = false;
+        for word in args.split_whitespace() {
+            let (remove, raw) = match word.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, word),
+            };
+            let tag = session_filter::normalize_tag(raw).ok_or_else(|| {
+                format!("invalid tag '{}': use letters, digits and - _ . /", word)
+            })?;
+            if remove {
+                changed |= meta.tags.contains(&tag);
+                meta.tags.retain(|t| t != &tag);
+            } else if !meta.tags.contains(&tag) {
+                meta.tags.push(tag);
+                changed = true;
+            }
+        }
+
+        let message = if meta.tags.is_empty() {
+            "Session tags: none".to_string()
+        } else {
+            let tags: Vec<String> = meta.tags.iter().map(|t| format!("#{}", t)).collect();
+            format!("Session tags: {}", tags.join(" "))
+        };
+        if changed {
+            self.session_dirty = true;
+            self.save_current_session();
+        }
+        Ok(message)
+    }
+
+    /// Most recent non-empty session created on `branch`, other than the
+    /// current one.
+    fn latest_session_for_branch(&self, branch: &str) -> Option<usize> {
+        let mut candidates: Vec<usize> = (0..self.sessions.len())
+            .filter(|&i| i != self.current_session)
+            .filter(|&i| {
+                self.session_meta
+                    .get(i)
+                    .is_some_and(|m| m.branch.as_deref() == Some(branch))
+            })
+            .collect();
+        candidates.sort_by_key(|&i| std::cmp::Reverse(self.session_meta[i].created));
+        candidates
+            .into_iter()
+            .find(|&i| self.session_meta[i].messages > 0)
+    }
+
+    fn refresh_branch_session_offer(&mut self) {
+        self.branch_session_offer = self
+            .observed_branch
+            .clone()
+            .filter(|b| !b.starts_with('_'))
+            .and_then(|b| self.latest_session_for_branch(&b));
+    }
+
+    /// Poll the checked-out branch and, when it changes, offer the session
+    /// last used on the new branch. Sandbox branch switches are ignored.
+    /// `git rev-parse` runs on a worker thread; its answer is picked up on a
+    /// later frame.
+    fn check_branch_switch(&mut self) {
+        if let Some(rx) = &self.branch_poll {
+            let branch = match rx.try_recv() {
+                Ok(branch) => branch,
+                Err(std::sync::mpsc::TryRecvError::Empty) => return,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => None,
+            };
+            self.branch_poll = None;
+            if let Some(branch) = branch
+                && !self.loading
+                && !self.sandbox_enabled
+                && self.observed_branch.as_deref() != Some(branch.as_str())
+            {
+                self.observed_branch = Some(branch);
+                self.refresh_branch_session_offer();
+            }
+            return;
+        }
+        if self.observed_branch.is_none()
+            || self.loading
+            || self.sandbox_enabled
+            || self.last_branch_check.elapsed() < BRANCH_POLL_INTERVAL
+        {
+            return;
+        }
+        self.last_branch_check = Instant::now();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(Self::current_git_branch().ok());
+        });
+        self.branch_poll = Some(rx);
+    }
+
+    /// Handle `/resume`: switch to the offered session for this branch.
+    pub(crate) fn resume_branch_session(&mut self) -> Result<String, String> {
+        let branch = self
+            .observed_branch
+            .clone()
+            .ok_or("not in a git repository")?;
+        let idx = self
+            .branch_session_offer
+            .filter(|&i| i != self.current_session)
+            .or_else(|| self.latest_session_for_branch(&branch))
+            .ok_or_else(|| format!("no earlier session on branch {}", branch))?;
+        self.save_current_session();
+        self.load_session(idx);
+        self.branch_session_offer = None;
+        Ok(format!(
+            "Resumed \"{}\" from branch {}.",
+            self.sessions.get(idx).cloned().unwrap_or_default(),
+            branch
+        ))
+    }
+
     fn maybe_update_session_title(&mut self, user_input: &str) {
         if self.chat_messages.is_empty() {
             let new_title = Self::truncate_title(user_input);
@@ -361,7 +793,7 @@ impl App {
         }
     }
 
-    fn config_path() -> PathBuf {
+    pub(crate) fn config_path() -> PathBuf {
         if let Ok(home) = env::var("HOME") {
             PathBuf::from(home).join(CONFIG_FILE)
         } else {
@@ -394,6 +826,8 @@ impl App {
 
         let (tx, rx) = mpsc::unbounded_channel();
         let (agent_tx, agent_rx) = mpsc::unbounded_channel();
+        let approval_rx = Self::install_approval_handler();
+        let selection_rx = Self::install_selection_handler();
 
         let (custom_model_name, custom_base_url) = if let Some(ref m) = selected_model {
             if m.provider == "Custom" {
@@ -416,7 +850,7 @@ impl App {
         let _ = std::fs::remove_file(&todo_file);
 
         let initial_messages: Vec<ChatMessage> = Vec::new();
-        let (sessions, session_paths) = Self::load_sessions_from_disk();
+        let (sessions, session_paths, session_meta) = Self::load_sessions_from_disk();
 
         let mut app = Self {
             state: AppState::Welcome,
@@ -444,6 +878,13 @@ impl App {
             },
             sessions,
             session_paths,
+            session_meta,
+            session_filter: String::new(),
+            session_filter_focused: false,
+            observed_branch: None,
+            last_branch_check: Instant::now(),
+            branch_poll: None,
+            branch_session_offer: None,
             current_session: 0,
             settings_api_key: api_key,
             settings_base_url,
@@ -472,15 +913,109 @@ impl App {
             modified_files: HashMap::new(),
             pending_tool_calls: Vec::new(),
             editor_state: crate::editor::editor::EditorState::new(),
+            approval_rx,
+            pending_approval: None,
+            selection_rx,
+            pending_selection: None,
+            trash_entries: Vec::new(),
+            trash_list_state: ListState::default(),
+            trash_show_all: false,
+            pending_context: Vec::new(),
+            speak_summaries: config.speak_summaries,
+            tts_command: config.tts_command,
+            speaker: crate::speech::Speaker::default(),
+            terminal_focused: true,
+            run_started: None,
+            disable_update_notice: config.disable_update_notice,
+            update_check: (!config.disable_update_notice).then(crate::update::spawn_update_check),
+            available_update: None,
+            diagnosis: None,
+            paste: None,
+            #[cfg(feature = "voice")]
+            voice: crate::voice::VoiceInput::default(),
         };
 
         // Always start with a fresh session; existing sessions are available via selector.
         app.create_new_session();
+        app.observed_branch = Self::current_git_branch().ok();
+        app.refresh_branch_session_offer();
 
         Ok(app)
     }
 
-    fn load_config() -> Config {
+    /// Route tool approval requests to the UI. The tool thread blocks until
+    /// the user answers the prompt shown by `process_events`.
+    fn install_approval_handler() -> mpsc::UnboundedReceiver<PendingApproval> {
+        let (approval_tx, approval_rx) = mpsc::unbounded_channel();
+        approval::set_handler(Box::new(move |request: &ApprovalRequest| {
+            let (respond, answer) = std::sync::mpsc::channel();
+            let pending = PendingApproval {
+                request: request.clone(),
+                respond,
+            };
+            if approval_tx.send(pending).is_err() {
+                return false;
+            }
+            answer.recv().unwrap_or(false)
+        }));
+        approval_rx
+    }
+
+    /// Route selection requests (e.g. project-wide find_replace hunks) to the
+    /// picker shown by `process_events`.
+    fn install_selection_handler() -> mpsc::UnboundedReceiver<PendingSelection> {
+        let (selection_tx, selection_rx) = mpsc::unbounded_channel();
+        approval::set_selection_handler(Box::new(move |request: &SelectionRequest| {
+            let (respond, answer) = std::sync::mpsc::channel();
+            if selection_tx
+                .send(PendingSelection::new(request.clone(), respond))
+                .is_err()
+            {
+                return None;
+            }
+            answer.recv().unwrap_or(None)
+        }));
+        selection_rx
+    }
+
+    /// Finish the pending selection: `true` applies the accepted items,
+    /// `false` cancels the whole operation.
+    pub(crate) fn resolve_selection(&mut self, apply: bool) {
+        if let Some(pending) = self.pending_selection.take() {
+            let accepted = pending.accepted.iter().filter(|a| **a).count();
+            let _ = pending
+                .respond
+                .send(apply.then_some(pending.accepted.clone()));
+            self.log_event(
+                "selection",
+                &format!(
+                    "{} {}: {} of {} accepted",
+                    if apply { "applied" } else { "cancelled" },
+                    pending.request.tool,
+                    accepted,
+                    pending.accepted.len()
+                ),
+            );
+        }
+    }
+
+    /// Answer the pending approval prompt, if any.
+    pub(crate) fn resolve_approval(&mut self, approved: bool) {
+        if let Some(pending) = self.pending_approval.take() {
+            let _ = pending.respond.send(approved);
+            self.log_event(
+                "approval",
+                &format!(
+                    "{} {}: {}",
+                    if approved { "approved" } else { "denied" },
+                    pending.request.tool,
+                    pending.request.action
+                ),
+            );
+        }
+    }
+
+    pub(crate) fn load_config() -> Config {
         let config_path = Self::config_path();
         if config_path.exists() {
             if let Ok(content) = std::fs::read_to_string(&config_path) {
@@ -494,6 +1029,9 @@ impl App {
             api_key,
             selected_model: None,
             theme_index: Some(0),
+            speak_summaries: false,
+            tts_command: None,
+            disable_update_notice: false,
         }
     }
 
@@ -502,6 +1040,9 @@ impl App {
             api_key: self.api_key.clone(),
             selected_model: self.selected_model.clone(),
             theme_index: Some(self.theme_index),
+            speak_summaries: self.speak_summaries,
+            tts_command: self.tts_command.clone(),
+            disable_update_notice: self.disable_update_notice,
         };
         let config_json = serde_json::to_string_pretty(&config)?;
         let config_path = Self::config_path();
@@ -517,9 +1058,16 @@ impl App {
             .as_secs();
         let name = format!("Session {}", ts);
         let path = Self::session_file_from_title(&name);
+        let meta = SessionMeta::now(
+            Self::workspace_dir().display().to_string(),
+            Self::current_git_branch().ok(),
+        );
+        self.pending_context.clear();
         self.sessions.push(name.clone());
         self.session_paths.push(path.clone());
+        self.session_meta.push(meta.clone());
         self.current_session = self.sessions.len().saturating_sub(1);
+        self.sync_trash_session();
         self.session_list_state.select(Some(self.current_session));
         self.chat_messages.clear();
         self.list_state.select(None);
@@ -532,7 +1080,7 @@ impl App {
             .unwrap_or_else(|_| std::path::PathBuf::from("."))
             .join(".pengy_todo.json");
         let _ = std::fs::remove_file(&todo_file);
-        Self::write_session_file(&path, &name, &[]);
+        Self::write_session_file(&path, &name, &[], &meta);
     }
 
     pub(crate) fn get_available_models() -> Vec<ModelOption> {
@@ -729,8 +1277,14 @@ impl App {
         vec![
             ("/models", "select model"),
             ("/agents", "select agent"),
-            ("/sessions", "switch session"),
+            ("/sessions", "switch session (optional filter query)"),
+            ("/tag", "tag this session (/tag bug-123, /tag -old)"),
+            ("/resume", "resume the last session on this git branch"),
+            ("/trash", "browse and restore deleted or overwritten files"),
             ("/new", "create new session"),
+            ("/diagnose", "root-cause an error or stack trace"),
+            ("/speak", "speak summaries of long or unwatched runs"),
+            ("/paste", "attach clipboard text to the next message"),
             ("/theme", "cycle theme"),
             ("/settings", "configure API key / model / base URL"),
             (
@@ -1149,6 +1703,7 @@ impl App {
         self.maybe_update_session_title(&user_input);
         self.chat_input.clear();
         self.input_cursor = 0;
+        let user_input = self.attach_pending_context(user_input);
 
         self.chat_messages
             .push(ChatMessage::User(user_input.clone()));
@@ -1157,6 +1712,7 @@ impl App {
         self.error = None;
         self.user_scrolled = false;
         self.last_token_usage = None;
+        self.run_started = Some(Instant::now());
 
         let tx = self.tx.clone();
 
@@ -1249,12 +1805,236 @@ impl App {
         Ok(())
     }
 
+    /// Start reading the clipboard for `/paste`. The clipboard utilities can
+    /// hang, so the read runs on a worker thread and `poll_paste` attaches
+    /// the text once it arrives.
+    pub(crate) fn paste_clipboard(&mut self, label: &str) -> Result<(), String> {
+        if self.paste.is_some() {
+            return Err("still reading the clipboard".to_string());
+        }
+        self.paste = Some((label.to_string(), crate::clipboard::spawn_read()));
+        Ok(())
+    }
+
+    /// Attach a finished `/paste` read, reporting the outcome in the chat.
+    fn poll_paste(&mut self) {
+        let Some((label, rx)) = &self.paste else {
+            return;
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("clipboard reader stopped unexpectedly".to_string())
+            }
+        };
+        let label = label.clone();
+        self.paste = None;
+        let message = match result.and_then(|text| self.attach_paste(&label, &text)) {
+            Ok(msg) => ChatMessage::Assistant(msg),
+            Err(err) => ChatMessage::Error(format!("[paste] {}", err)),
+        };
+        self.chat_messages.push(message);
+        if !self.user_scrolled {
+            self.list_state.select(Some(self.chat_messages.len() - 1));
+        }
+    }
+
+    /// Queue clipboard `text` as a context block for the next message.
+    /// Returns a confirmation for the chat.
+    fn attach_paste(&mut self, label: &str, text: &str) -> Result<String, String> {
+        let text = text.trim_end_matches(['\n', '\r']);
+        if text.trim().is_empty() {
+            return Err("the clipboard is empty".to_string());
+        }
+
+        let total_chars = text.chars().count();
+        let mut content: String = text.chars().take(MAX_PASTE_CHARS).collect();
+        if total_chars > MAX_PASTE_CHARS {
+            content.push_str(&format!(
+                "\n... [truncated {} more characters]",
+                total_chars - MAX_PASTE_CHARS
+            ));
+        }
+        let label = if !label.is_empty() {
+            label.to_string()
+        } else if self.pending_context.is_empty() {
+            "clipboard".to_string()
+        } else {
+            format!("clipboard #{}", self.pending_context.len() + 1)
+        };
+
+        let message = format!(
+            "Attached {} ({} lines, {} chars); it will be sent with your next message. Use /paste clear to drop it.",
+            label,
+            content.lines().count(),
+            total_chars.min(MAX_PASTE_CHARS)
+        );
+        self.pending_context.push(ContextBlock { label, content });
+        Ok(message)
+    }
+
+    /// Start turning an error or stack trace into a root-cause analysis
+    /// request. The trace comes from `arg`, otherwise from blocks queued with
+    /// /paste, otherwise from the clipboard. Reading the clipboard can hang
+    /// and gathering code, blame and tests walks the workspace, so both run
+    /// on a worker thread and the prompt is picked up by `poll_diagnosis`.
+    pub(crate) fn prepare_diagnosis(&mut self, arg: &str) -> Result<(), String> {
+        let trace = if !arg.trim().is_empty() {
+            Some(arg.to_string())
+        } else if !self.pending_context.is_empty() {
+            Some(
+                self.pending_context
+                    .drain(..)
+                    .map(|block| block.content)
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            )
+        } else {
+            None
+        };
+        let workspace = std::env::current_dir().map_err(|err| err.to_string())?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let trace = match trace {
+                Some(trace) => Ok(trace),
+                None => crate::clipboard::read_text().map_err(|err| {
+                    format!(
+                        "nothing to diagnose: pass the error inline, /paste it first, or copy it ({})",
+                        err
+                    )
+                }),
+            };
+            let _ = tx.send(trace.and_then(|trace| {
+                diagnose::build_prompt(&trace, &workspace).map_err(|err| err.to_string())
+            }));
+        });
+        self.diagnosis = Some(rx);
+        self.chat_input.clear();
+        self.input_cursor = 0;
+        self.show_command_hints = false;
+        self.loading = true;
+        self.error = None;
+        Ok(())
+    }
+
+    /// Place a finished `/diagnose` prompt in the input. Returns true when it
+    /// is ready for `send_message`; a diagnosis stopped with Ctrl+C in the
+    /// meantime is dropped.
+    pub(crate) fn poll_diagnosis(&mut self) -> bool {
+        let Some(rx) = &self.diagnosis else {
+            return false;
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("diagnosis worker stopped unexpectedly".to_string())
+            }
+        };
+        self.diagnosis = None;
+        if !self.loading {
+            return false;
+        }
+        match result {
+            Ok(prompt) => {
+                self.chat_input = prompt;
+                self.input_cursor = self.chat_input.len();
+                true
+            }
+            Err(err) => {
+                self.loading = false;
+                self.chat_messages
+                    .push(ChatMessage::Error(format!("[diagnose] {}", err)));
+                false
+            }
+        }
+    }
+
+    /// Prefix `input` with any pasted context blocks, consuming them.
+    fn attach_pending_context(&mut self, input: String) -> String {
+        if self.pending_context.is_empty() {
+            return input;
+        }
+        let blocks: Vec<String> = self
+            .pending_context
+            .drain(..)
+            .map(|block| block.render())
+            .collect();
+        format!("{}\n\n{}", blocks.join("\n\n"), input)
+    }
+
+    /// Start or stop push-to-talk recording.
+    #[cfg(feature = "voice")]
+    pub(crate) fn toggle_voice(&mut self) {
+        let model_base_url = self
+            .selected_model
+            .as_ref()
+            .map(|m| App::normalize_base_url(&m.base_url))
+            .unwrap_or_default();
+        if let Err(err) = self.voice.toggle(&self.api_key, &model_base_url) {
+            self.chat_messages
+                .push(ChatMessage::Error(format!("[voice] {}", err)));
+        }
+    }
+
+    /// Insert a finished transcript at the cursor.
+    #[cfg(feature = "voice")]
+    fn process_voice(&mut self) {
+        match self.voice.poll() {
+            Some(Ok(text)) => {
+                let at = if self.chat_input.is_char_boundary(self.input_cursor) {
+                    self.input_cursor
+                } else {
+                    self.chat_input.len()
+                };
+                let needs_space = self.chat_input[..at]
+                    .chars()
+                    .last()
+                    .is_some_and(|c| !c.is_whitespace());
+                let text = if needs_space {
+                    format!(" {}", text)
+                } else {
+                    text
+                };
+                self.chat_input.insert_str(at, &text);
+                self.input_cursor = at + text.len();
+                self.show_command_hints = self.chat_input.starts_with('/');
+            }
+            Some(Err(err)) => self
+                .chat_messages
+                .push(ChatMessage::Error(format!("[voice] {}", err))),
+            None => {}
+        }
+    }
+
     pub(crate) fn process_events(&mut self) {
         let mut changed = false;
+        #[cfg(feature = "voice")]
+        self.process_voice();
+        self.check_branch_switch();
+        self.poll_paste();
+        if let Some(rx) = &self.update_check
+            && let Ok(result) = rx.try_recv()
+        {
+            self.available_update = result;
+            self.update_check = None;
+        }
         while let Ok(agent) = self.agent_rx.try_recv() {
             self.agent = Some(agent);
         }
 
+        if self.pending_approval.is_none()
+            && let Ok(pending) = self.approval_rx.try_recv()
+        {
+            self.pending_approval = Some(pending);
+        }
+        if self.pending_selection.is_none()
+            && let Ok(pending) = self.selection_rx.try_recv()
+        {
+            self.pending_selection = Some(pending);
+        }
+
         while let Ok(event) = self.rx.try_recv() {
             match event {
                 AgentEvent::Step { .. } => {}
@@ -1372,6 +2152,19 @@ impl App {
                     self.log_event("assistant", &content);
                     self.loading = false;
                     changed = true;
+                    let elapsed = self
+                        .run_started
+                        .take()
+                        .map(|started| started.elapsed())
+                        .unwrap_or_default();
+                    if self.speak_summaries
+                        && crate::speech::should_speak(self.terminal_focused, elapsed)
+                        && let Some(summary) = crate::speech::summary_sentence(&content)
+                        && let Err(err) = self.speaker.speak(&summary, self.tts_command.as_deref())
+                    {
+                        self.chat_messages
+                            .push(ChatMessage::Error(format!("[speak] {}", err)));
+                    }
                     if self.sandbox_enabled {
                         match self.maybe_auto_commit_sandbox() {
                             Ok(Some(msg)) => {
@@ -1464,7 +2257,8 @@ impl App {
 
 #[cfg(test)]
 mod tests {
-    use super::App;
+    use super::{App, ContextBlock, PendingSelection, SelectionRow};
+    use pengy_agent::tool::approval::approval::{SelectionItem, SelectionRequest};
 
     #[test]
     fn sanitize_branch_name_normalizes_and_prefixes_are_stable() {
@@ -1473,4 +2267,69 @@ mod tests {
         assert_eq!(App::sanitize_branch_name("___weird___name___"), "___weird___name___");
         assert_eq!(App::sanitize_branch_name(""), "session");
     }
+
+    #[test]
+    fn session_meta_counts_messages_without_loading_them() {
+        let dir = std::env::temp_dir().join(format!("pengy-session-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session.json");
+        std::fs::write(
+            &path,
+            r#"{"title": "Old", "messages": [{"role": "user", "content": "hi"}], "branch": "main"}"#,
+        )
+        .unwrap();
+
+        let (title, meta) = App::read_session_meta(&path).unwrap();
+        assert_eq!(title, "Old");
+        assert_eq!(meta.messages, 1);
+        assert_eq!(meta.branch.as_deref(), Some("main"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pasted_context_fence_outlasts_backticks_in_content() {
+        let block = ContextBlock {
+            label: "clipboard".to_string(),
+            content: "```rust\nfn main() {}\n```".to_string(),
+        };
+        assert_eq!(
+            block.render(),
+            "[Pasted context: clipboard]\n````\n```rust\nfn main() {}\n```\n````"
+        );
+    }
+
+    #[test]
+    fn selection_picker_groups_items_and_toggles_groups() {
+        let item = |group: &str, label: &str| SelectionItem {
+            group: group.to_string(),
+            label: label.to_string(),
+            detail: String::new(),
+        };
+        let request = SelectionRequest {
+            tool: "find_replace".to_string(),
+            title: "test".to_string(),
+            items: vec![item("a.rs", "L1"), item("a.rs", "L2"), item("b.rs", "L1")],
+        };
+        let (respond, _answer) = std::sync::mpsc::channel();
+        let mut pending = PendingSelection::new(request, respond);
+
+        assert!(
+            pending.rows
+                == vec![
+                    SelectionRow::Group { start: 0, end: 2 },
+                    SelectionRow::Item(0),
+                    SelectionRow::Item(1),
+                    SelectionRow::Group { start: 2, end: 3 },
+                    SelectionRow::Item(2),
+                ]
+        );
+
+        // Toggling a group heading flips all of its hunks
+        pending.toggle_current();
+        assert_eq!(pending.accepted, vec![false, false, true]);
+
+        pending.move_cursor(2);
+        pending.toggle_current();
+        assert_eq!(pending.accepted, vec![false, true, true]);
+    }
 }
@@ -0,0 +1,149 @@
+//! Read the system clipboard for `/paste` using the platform's clipboard
+//! utilities: pbpaste on macOS, PowerShell on Windows and wl-paste, xclip,
+//! xsel or termux-clipboard-get on Linux/BSD.
+//!
+//! A clipboard owner that never answers would block these utilities
+//! indefinitely, so each one is killed after [`READ_TIMEOUT`]. Callers still
+//! read on a worker thread ([`spawn_read`]) to keep the UI responsive.
+
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How long one clipboard utility may take before it is killed.
+const READ_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Candidate commands in the order they are tried on this platform.
+fn candidates() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbpaste", &[])]
+    } else if cfg!(target_os = "windows") {
+        vec![(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "Get-Clipboard -Raw",
+            ],
+        )]
+    } else {
+        let mut list: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            list.push(("wl-paste", &["--no-newline", "--type", "text"]));
+        }
+        list.push(("xclip", &["-selection", "clipboard", "-out"]));
+        list.push(("xsel", &["--clipboard", "--output"]));
+        list.push(("termux-clipboard-get", &[]));
+        list
+    }
+}
+
+/// Return the clipboard's text, or a message explaining why it could not be
+/// read.
+pub(crate) fn read_text() -> Result<String, String> {
+    let mut tried = Vec::new();
+    for (program, args) in candidates() {
+        match output_within(program, args, READ_TIMEOUT) {
+            Ok(None) => tried.push(format!(
+                "{} did not answer within {}s",
+                program,
+                READ_TIMEOUT.as_secs()
+            )),
+            Ok(Some(out)) if out.status.success() => {
+                return String::from_utf8(out.stdout)
+                    .map(|text| text.replace("\r\n", "\n"))
+                    .map_err(|_| "clipboard does not contain text".to_string());
+            }
+            Ok(Some(out)) => {
+                let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+                tried.push(if stderr.is_empty() {
+                    format!("{} exited with {}", program, out.status)
+                } else {
+                    format!("{}: {}", program, stderr)
+                });
+            }
+            Err(_) => tried.push(format!("{} not installed", program)),
+        }
+    }
+    Err(format!(
+        "could not read the clipboard ({})",
+        tried.join("; ")
+    ))
+}
+
+/// Read the clipboard on a worker thread; the result arrives on the
+/// returned channel.
+pub(crate) fn spawn_read() -> Receiver<Result<String, String>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(read_text());
+    });
+    rx
+}
+
+/// Run `program` and collect its output, or kill it and return `None` once
+/// `timeout` passes. Output is drained on separate threads so a large
+/// clipboard cannot fill the pipe and stall the child.
+fn output_within(
+    program: &str,
+    args: &[&str],
+    timeout: Duration,
+) -> std::io::Result<Option<Output>> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    Ok(Some(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    }))
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stuck_commands_are_killed() {
+        let started = Instant::now();
+        let out = output_within("sleep", &["5"], Duration::from_millis(100)).unwrap();
+        assert!(out.is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let out = output_within("echo", &["hi"], Duration::from_secs(5))
+            .unwrap()
+            .unwrap();
+        assert_eq!(out.stdout, b"hi\n");
+    }
+}
@@ -0,0 +1,607 @@
+//! `pengy doctor`: check the local setup and explain how to fix what is
+//! wrong.
+//!
+//! Each check prints a pass, warning or failure line, and non-passing
+//! checks add a remediation hint. The command exits non-zero if any check
+//! failed, so it can also be used in setup scripts.
+
+use crate::app::{App, Config};
+use crate::constants::DEFAULT_BASE_URL;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const DATA_DIR: &str = ".pengy";
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(5);
+/// Data directory size above which a cleanup is suggested.
+const DATA_DIR_WARN_BYTES: u64 = 500 * 1024 * 1024;
+/// Terminals known to support setting the clipboard with OSC 52, matched
+/// against `TERM_PROGRAM` and `TERM`.
+const OSC52_TERMINALS: &[(&str, &str)] = &[
+    ("iTerm.app", "iTerm2"),
+    ("WezTerm", "WezTerm"),
+    ("ghostty", "Ghostty"),
+    ("xterm-ghostty", "Ghostty"),
+    ("xterm-kitty", "kitty"),
+    ("alacritty", "Alacritty"),
+    ("foot", "foot"),
+    ("foot-extra", "foot"),
+    ("contour", "Contour"),
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Human-readable size such as `12.3 MB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Total size of the files under `path`; symlinks are not followed.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Whether the config file parses; the parsed config is returned so later
+/// checks can use the configured key and provider.
+fn check_config(path: &Path) -> (Check, Option<Config>) {
+    const NAME: &str = "Config";
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return (
+                Check::warn(
+                    NAME,
+                    format!("{} does not exist", path.display()),
+                    "run `pengy` and save your settings with /settings to create it",
+                ),
+                None,
+            );
+        }
+        Err(e) => {
+            return (
+                Check::fail(
+                    NAME,
+                    format!("cannot read {}: {}", path.display(), e),
+                    "check the file's permissions",
+                ),
+                None,
+            );
+        }
+    };
+    match serde_json::from_str::<Config>(&content) {
+        Ok(config) => (
+            Check::pass(NAME, format!("{} is valid", path.display())),
+            Some(config),
+        ),
+        // pengy silently falls back to defaults for an invalid config, so
+        // this is the only place the problem is reported.
+        Err(e) => (
+            Check::fail(
+                NAME,
+                format!("{} is invalid: {}", path.display(), e),
+                "fix the JSON by hand, or delete the file and configure pengy again with /settings",
+            ),
+            None,
+        ),
+    }
+}
+
+/// The key pengy will use: the config's when a config exists, otherwise
+/// `API_KEY`.
+fn effective_api_key(config: Option<&Config>) -> String {
+    match config {
+        Some(config) => config.api_key.trim().to_string(),
+        None => env::var("API_KEY").unwrap_or_default().trim().to_string(),
+    }
+}
+
+fn check_api_key(config: Option<&Config>) -> Check {
+    const NAME: &str = "API key";
+    let key = effective_api_key(config);
+    if key.is_empty() {
+        let hint = if config.is_some() {
+            "set a key with /settings (API_KEY is only used when there is no config file)"
+        } else {
+            "set a key with /settings in pengy, or export API_KEY"
+        };
+        return Check::fail(NAME, "no API key is configured", hint);
+    }
+    let source = if config.is_some() {
+        "config"
+    } else {
+        "API_KEY"
+    };
+    let tail: String = key
+        .chars()
+        .skip(key.chars().count().saturating_sub(4))
+        .collect();
+    Check::pass(NAME, format!("set from {} (ends in …{})", source, tail))
+}
+
+async fn probe_provider(base_url: &str, api_key: &str) -> Result<reqwest::StatusCode, String> {
+    let client = reqwest::Client::builder()
+        .timeout(PROVIDER_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut request = client.get(format!("{}/models", base_url.trim_end_matches('/')));
+    if !api_key.is_empty() {
+        request = request.bearer_auth(api_key);
+    }
+    request
+        .send()
+        .await
+        .map(|response| response.status())
+        .map_err(|e| {
+            // reqwest's own message omits the cause (DNS, TLS, timeout...).
+            let mut message = e.to_string();
+            let mut source = std::error::Error::source(&e);
+            while let Some(cause) = source {
+                message.push_str(&format!(": {}", cause));
+                source = cause.source();
+            }
+            message
+        })
+}
+
+fn check_provider(config: Option<&Config>) -> Check {
+    const NAME: &str = "Provider";
+    let base_url = config
+        .and_then(|config| config.selected_model.as_ref())
+        .map(|model| model.base_url.clone())
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+    let api_key = effective_api_key(config);
+    let status = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("failed to create async runtime: {}", e))
+        .and_then(|rt| rt.block_on(probe_provider(&base_url, &api_key)));
+    match status {
+        Ok(status) if status.is_success() => {
+            Check::pass(NAME, format!("{} is reachable", base_url))
+        }
+        Ok(status) if status.as_u16() == 401 || status.as_u16() == 403 => Check::fail(
+            NAME,
+            format!("{} rejected the API key ({})", base_url, status),
+            "check that the key in /settings belongs to this provider and has not been revoked",
+        ),
+        Ok(status) => Check::warn(
+            NAME,
+            format!("{} answered {} for /models", base_url, status),
+            "the host is reachable; if requests fail, check the base URL with /baseurl",
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("cannot reach {}: {}", base_url, e),
+            "check your network connection and HTTPS_PROXY, and the base URL with /baseurl",
+        ),
+    }
+}
+
+fn check_git() -> Check {
+    const NAME: &str = "Git";
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+    };
+    let Some(version) = git(&["--version"]) else {
+        return Check::fail(
+            NAME,
+            "git is not installed or not on PATH",
+            "install git; sandbox mode, session branches and the git tools depend on it",
+        );
+    };
+    if git(&["rev-parse", "--is-inside-work-tree"]).as_deref() != Some("true") {
+        return Check::warn(
+            NAME,
+            format!("{}, but the current directory is not a repository", version),
+            "run pengy inside a git repository (or `git init`) to use /sandbox and branch-linked sessions",
+        );
+    }
+    Check::pass(NAME, version)
+}
+
+fn supports_truecolor(var: &dyn Fn(&str) -> Option<String>) -> bool {
+    matches!(
+        var("COLORTERM")
+            .as_deref()
+            .map(str::to_lowercase)
+            .as_deref(),
+        Some("truecolor" | "24bit")
+    )
+}
+
+fn check_truecolor(var: &dyn Fn(&str) -> Option<String>) -> Check {
+    const NAME: &str = "Truecolor";
+    if supports_truecolor(var) {
+        return Check::pass(NAME, "COLORTERM advertises 24-bit color");
+    }
+    if var("TERM_PROGRAM").as_deref() == Some("Apple_Terminal") {
+        return Check::warn(
+            NAME,
+            "Terminal.app does not support 24-bit color",
+            "themes will use approximate colors; iTerm2, WezTerm, kitty or Ghostty render them exactly",
+        );
+    }
+    Check::warn(
+        NAME,
+        format!(
+            "COLORTERM is {}",
+            var("COLORTERM").unwrap_or_else(|| "not set".to_string())
+        ),
+        "if your terminal supports 24-bit color, export COLORTERM=truecolor; otherwise themes use approximate colors",
+    )
+}
+
+/// Name of the terminal if it is known to support OSC 52.
+fn osc52_terminal(var: &dyn Fn(&str) -> Option<String>) -> Option<&'static str> {
+    if var("WT_SESSION").is_some() {
+        return Some("Windows Terminal");
+    }
+    if var("KITTY_WINDOW_ID").is_some() {
+        return Some("kitty");
+    }
+    ["TERM_PROGRAM", "TERM"].iter().find_map(|key| {
+        let value = var(key)?;
+        OSC52_TERMINALS
+            .iter()
+            .find(|(id, _)| *id == value)
+            .map(|(_, name)| *name)
+    })
+}
+
+fn check_osc52(var: &dyn Fn(&str) -> Option<String>) -> Check {
+    const NAME: &str = "OSC 52";
+    let terminal = osc52_terminal(var);
+    if var("TMUX").is_some() {
+        // tmux only forwards OSC 52 to the outer terminal when allowed to.
+        let setting = Command::new("tmux")
+            .args(["show-options", "-gv", "set-clipboard"])
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .unwrap_or_default();
+        if setting != "on" && setting != "external" {
+            return Check::warn(
+                NAME,
+                "tmux is not forwarding clipboard escape sequences",
+                "add `set -g set-clipboard on` to ~/.tmux.conf and reload it",
+            );
+        }
+    }
+    match terminal {
+        Some(name) => Check::pass(
+            NAME,
+            format!("{} supports clipboard escape sequences", name),
+        ),
+        None => Check::warn(
+            NAME,
+            format!(
+                "cannot tell whether this terminal ({}) supports OSC 52",
+                var("TERM_PROGRAM")
+                    .or_else(|| var("TERM"))
+                    .unwrap_or_else(|| "unknown".to_string())
+            ),
+            "copying over SSH needs OSC 52; iTerm2, WezTerm, kitty, Alacritty, foot, Ghostty and Windows Terminal support it",
+        ),
+    }
+}
+
+/// Whether every saved session still loads; unreadable ones are silently
+/// left out of /sessions.
+fn check_sessions(dir: &Path) -> Check {
+    const NAME: &str = "Session index";
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Check::pass(NAME, "no saved sessions yet");
+    };
+    let files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    let broken: Vec<String> = files
+        .iter()
+        .filter(|path| App::read_session_file(path).is_none())
+        .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+        .collect();
+    if broken.is_empty() {
+        return Check::pass(NAME, format!("{} sessions load", files.len()));
+    }
+    let mut listed = broken
+        .iter()
+        .take(3)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if broken.len() > 3 {
+        listed.push_str(", …");
+    }
+    Check::warn(
+        NAME,
+        format!(
+            "{} of {} sessions cannot be read ({})",
+            broken.len(),
+            files.len(),
+            listed
+        ),
+        format!(
+            "they are hidden from /sessions; move them out of {} or fix their JSON",
+            dir.display()
+        ),
+    )
+}
+
+/// Size and writability of the data directory. Nothing is created: a
+/// missing directory is reported as such, and writability is read from the
+/// permissions of the directory (or of its parent when it does not exist).
+fn check_data_dir(dir: &Path) -> Check {
+    const NAME: &str = "Data dir";
+    const HINT: &str = "fix the directory's permissions; sessions, logs and trash are stored there";
+    let metadata = match fs::metadata(dir) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let parent = dir
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            return match fs::metadata(parent) {
+                Ok(parent_meta)
+                    if parent_meta.is_dir() && !parent_meta.permissions().readonly() =>
+                {
+                    Check::pass(
+                        NAME,
+                        format!(
+                            "{} not created yet; it is created on first use",
+                            dir.display()
+                        ),
+                    )
+                }
+                _ => Check::fail(
+                    NAME,
+                    format!(
+                        "{} not created yet and {} is not writable",
+                        dir.display(),
+                        parent.display()
+                    ),
+                    HINT,
+                ),
+            };
+        }
+        Err(e) => {
+            return Check::fail(NAME, format!("cannot read {}: {}", dir.display(), e), HINT);
+        }
+    };
+    if !metadata.is_dir() {
+        return Check::fail(
+            NAME,
+            format!("{} is not a directory", dir.display()),
+            "move the file out of the way; pengy stores sessions, logs and trash there",
+        );
+    }
+    if metadata.permissions().readonly() {
+        return Check::fail(NAME, format!("{} is not writable", dir.display()), HINT);
+    }
+
+    let total = dir_size(dir);
+    let trash = dir_size(&dir.join("trash"));
+    let logs: u64 = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with("pengy_json_"))
+        })
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum();
+    let detail = format!(
+        "{} uses {} (trash {}, run logs {})",
+        dir.display(),
+        format_size(total),
+        format_size(trash),
+        format_size(logs)
+    );
+    if total > DATA_DIR_WARN_BYTES {
+        return Check::warn(
+            NAME,
+            detail,
+            format!(
+                "delete old {0}/pengy_json_*.json run logs, and empty {0}/trash once nothing there needs restoring",
+                dir.display()
+            ),
+        );
+    }
+    Check::pass(NAME, detail)
+}
+
+pub(crate) fn print_doctor_usage() {
+    eprintln!("\nUsage: pengy doctor");
+    eprintln!("\nChecks the config file, API key, provider reachability, git, terminal");
+    eprintln!("capabilities, saved sessions and the .pengy data directory, and prints a");
+    eprintln!("remediation hint for each problem. Exits with status 1 if a check fails.");
+}
+
+/// Run every check and print the report; returns whether all checks passed
+/// or only warned.
+pub(crate) fn run_doctor() -> bool {
+    let var = |key: &str| env::var(key).ok().filter(|value| !value.is_empty());
+    let workspace = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+    let (config_check, config) = check_config(&App::config_path());
+    let checks = vec![
+        config_check,
+        check_api_key(config.as_ref()),
+        check_provider(config.as_ref()),
+        check_git(),
+        check_truecolor(&var),
+        check_osc52(&var),
+        check_sessions(&App::session_dir()),
+        check_data_dir(&workspace.join(DATA_DIR)),
+    ];
+
+    println!("pengy doctor ({})\n", env!("CARGO_PKG_VERSION"));
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0);
+    for check in &checks {
+        let mark = match check.status {
+            Status::Pass => "✓",
+            Status::Warn => "!",
+            Status::Fail => "✗",
+        };
+        println!(
+            "  {} {:<width$}  {}",
+            mark,
+            check.name,
+            check.detail,
+            width = width
+        );
+        if let Some(hint) = &check.hint {
+            println!("    {:<width$}  → {}", "", hint, width = width);
+        }
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let failed = count(Status::Fail);
+    println!(
+        "\n{} passed, {} warnings, {} failed",
+        count(Status::Pass),
+        count(Status::Warn),
+        failed
+    );
+    failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_and_terminal_detection() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+
+        let env_of = |pairs: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert!(supports_truecolor(&env_of(&[("COLORTERM", "TrueColor")])));
+        assert!(!supports_truecolor(&env_of(&[("TERM", "xterm-256color")])));
+        assert_eq!(
+            osc52_terminal(&env_of(&[("TERM_PROGRAM", "WezTerm")])),
+            Some("WezTerm")
+        );
+        assert_eq!(
+            osc52_terminal(&env_of(&[("TERM", "xterm-kitty")])),
+            Some("kitty")
+        );
+        assert_eq!(
+            osc52_terminal(&env_of(&[("TERM_PROGRAM", "Apple_Terminal")])),
+            None
+        );
+
+        let dir = env::temp_dir().join(format!("pengy-doctor-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("trash/s1")).unwrap();
+        fs::write(dir.join("pengy_json_a.json"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("trash/s1/file"), vec![0u8; 50]).unwrap();
+        assert_eq!(dir_size(&dir), 150);
+        let check = check_data_dir(&dir);
+        assert_eq!(check.status, Status::Pass);
+        assert!(check.detail.contains("trash 50 B, run logs 100 B"));
+
+        let missing = dir.join(".pengy");
+        let check = check_data_dir(&missing);
+        assert_eq!(check.status, Status::Pass);
+        assert!(check.detail.contains("not created yet"));
+        assert!(!missing.exists());
+
+        let mut permissions = fs::metadata(&dir).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&dir, permissions.clone()).unwrap();
+        assert_eq!(check_data_dir(&dir).status, Status::Fail);
+        assert_eq!(check_data_dir(&missing).status, Status::Fail);
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&dir, permissions).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
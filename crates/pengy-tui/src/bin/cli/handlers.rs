@@ -43,10 +43,19 @@ fn dispatch_slash_command(app: &mut App, cmd: &str, previous_state: AppState) {
     if cmd.starts_with("/sessions") {
         app.previous_state = Some(previous_state);
         app.state = AppState::SessionSelector;
-        app.session_list_state.select(Some(
-            app.current_session
-                .min(app.sessions.len().saturating_sub(1)),
-        ));
+        app.session_filter = cmd.trim_start_matches("/sessions").trim().to_string();
+        app.session_filter_focused = false;
+        app.select_current_in_session_filter();
+        reset_input(app);
+        return;
+    }
+
+    if cmd.starts_with("/trash") {
+        app.previous_state = Some(previous_state);
+        app.state = AppState::TrashBrowser;
+        app.trash_show_all = false;
+        app.trash_list_state.select(Some(0));
+        app.refresh_trash_entries();
         reset_input(app);
         return;
     }
@@ -54,6 +63,24 @@ fn dispatch_slash_command(app: &mut App, cmd: &str, previous_state: AppState) {
     handle_command_inline(app, cmd, previous_state);
 }
 
+/// `/diagnose` starts building its prompt in the background; the main loop
+/// sends it once `App::poll_diagnosis` reports it ready.
+fn run_diagnose(app: &mut App, cmd: &str) {
+    if app.loading {
+        app.chat_messages.push(ChatMessage::Error(
+            "[diagnose] wait for the current response to finish".to_string(),
+        ));
+        reset_input(app);
+        return;
+    }
+    let arg = cmd.trim_start_matches("/diagnose").trim().to_string();
+    if let Err(err) = app.prepare_diagnosis(&arg) {
+        app.chat_messages
+            .push(ChatMessage::Error(format!("[diagnose] {}", err)));
+        reset_input(app);
+    }
+}
+
 pub(crate) fn handle_welcome_key(
     app: &mut App,
     key: crossterm::event::KeyCode,
@@ -62,7 +89,13 @@ pub(crate) fn handle_welcome_key(
     match key {
         crossterm::event::KeyCode::Esc => return Err("quit".into()),
         crossterm::event::KeyCode::Enter => {
-            if app.chat_input.starts_with('/') {
+            if app.chat_input.starts_with("/diagnose") {
+                if app.initialize_model().is_ok() {
+                    app.state = AppState::Chat;
+                    let cmd = app.chat_input.clone();
+                    run_diagnose(app, &cmd);
+                }
+            } else if app.chat_input.starts_with('/') {
                 let cmd = app.chat_input.clone();
                 dispatch_slash_command(app, &cmd, AppState::Welcome);
             } else if app.initialize_model().is_ok() {
@@ -86,7 +119,10 @@ pub(crate) fn handle_chat_key(
     match key {
         crossterm::event::KeyCode::Esc => return Err("quit".into()),
         crossterm::event::KeyCode::Enter => {
-            if app.chat_input.starts_with('/') {
+            if app.chat_input.starts_with("/diagnose") {
+                let cmd = app.chat_input.clone();
+                run_diagnose(app, &cmd);
+            } else if app.chat_input.starts_with('/') {
                 let cmd = app.chat_input.clone();
                 dispatch_slash_command(app, &cmd, AppState::Chat);
             } else if !app.loading && !app.chat_input.trim().is_empty() {
@@ -137,6 +173,16 @@ pub(crate) fn handle_state_key(
     key: KeyCode,
     rt: &tokio::runtime::Runtime,
 ) -> Result<bool, Box<dyn Error>> {
+    // A pending approval prompt captures all keys until it is answered
+    if app.pending_approval.is_some() {
+        handle_approval_key(app, key);
+        return Ok(false);
+    }
+    if app.pending_selection.is_some() {
+        handle_selection_key(app, key);
+        return Ok(false);
+    }
+
     let should_quit = match app.state {
         AppState::Welcome => matches!(
             handle_welcome_key(app, key, rt),
@@ -147,6 +193,7 @@ pub(crate) fn handle_state_key(
             Err(e) if e.to_string() == "quit"
         ),
         AppState::SessionSelector => handle_session_selector_key(app, key),
+        AppState::TrashBrowser => handle_trash_browser_key(app, key),
         AppState::ModelSelector => handle_model_selector_key(app, key),
         AppState::ThemeSelector => handle_theme_selector_key(app, key),
         AppState::AgentSelector => handle_agent_selector_key(app, key),
@@ -172,22 +219,95 @@ pub(crate) fn handle_state_key(
     Ok(should_quit)
 }
 
+fn handle_approval_key(app: &mut App, key: KeyCode) {
+    match key {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => app.resolve_approval(true),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.resolve_approval(false),
+        _ => {}
+    }
+}
+
+fn handle_selection_key(app: &mut App, key: KeyCode) {
+    let Some(pending) = app.pending_selection.as_mut() else {
+        return;
+    };
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => pending.move_cursor(1),
+        KeyCode::Char('k') | KeyCode::Up => pending.move_cursor(-1),
+        KeyCode::PageDown => pending.move_cursor(10),
+        KeyCode::PageUp => pending.move_cursor(-10),
+        KeyCode::Char(' ') => pending.toggle_current(),
+        KeyCode::Char('a') => pending.toggle_all(),
+        KeyCode::Enter => app.resolve_selection(true),
+        KeyCode::Esc => app.resolve_selection(false),
+        _ => {}
+    }
+}
+
+fn handle_trash_browser_key(app: &mut App, key: KeyCode) -> bool {
+    match key {
+        KeyCode::Esc => {
+            app.state = app.previous_state.clone().unwrap_or(AppState::Welcome);
+        }
+        KeyCode::Enter => app.restore_selected_trash_entry(),
+        KeyCode::Tab => {
+            app.trash_show_all = !app.trash_show_all;
+            app.trash_list_state.select(Some(0));
+            app.refresh_trash_entries();
+        }
+        KeyCode::Char('j') | KeyCode::Down => {
+            let i = (app.trash_list_state.selected().unwrap_or(0) + 1)
+                .min(app.trash_entries.len().saturating_sub(1));
+            app.trash_list_state.select(Some(i));
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            let i = app
+                .trash_list_state
+                .selected()
+                .unwrap_or(0)
+                .saturating_sub(1);
+            app.trash_list_state.select(Some(i));
+        }
+        _ => {}
+    }
+    false
+}
+
 fn handle_session_selector_key(app: &mut App, key: KeyCode) -> bool {
+    let filtered = app.filtered_sessions();
     match key {
         KeyCode::Esc => {
             app.state = app.previous_state.clone().unwrap_or(AppState::Welcome);
+            app.session_filter_focused = false;
+            app.session_filter.clear();
+        }
+        KeyCode::Tab => {
+            app.session_filter_focused = !app.session_filter_focused;
         }
         KeyCode::Enter => {
-            if let Some(idx) = app.session_list_state.selected() {
-                if idx < app.sessions.len() {
-                    app.load_session(idx);
-                    app.state = app.previous_state.clone().unwrap_or(AppState::Welcome);
-                }
+            if app.session_filter_focused {
+                app.session_filter_focused = false;
+            } else if let Some(&idx) = app
+                .session_list_state
+                .selected()
+                .and_then(|sel| filtered.get(sel))
+            {
+                app.load_session(idx);
+                app.state = app.previous_state.clone().unwrap_or(AppState::Welcome);
+                app.session_filter.clear();
             }
         }
+        KeyCode::Char(c) if app.session_filter_focused => {
+            app.session_filter.push(c);
+            app.select_current_in_session_filter();
+        }
+        KeyCode::Backspace if app.session_filter_focused => {
+            app.session_filter.pop();
+            app.select_current_in_session_filter();
+        }
         KeyCode::Char('j') | KeyCode::Down => {
             let i = (app.session_list_state.selected().unwrap_or(0) + 1)
-                .min(app.sessions.len().saturating_sub(1));
+                .min(filtered.len().saturating_sub(1));
             app.session_list_state.select(Some(i));
         }
         KeyCode::Char('k') | KeyCode::Up => {
@@ -801,6 +921,7 @@ pub(crate) fn handle_command_inline(app: &mut App, cmd: &str, previous_state: Ap
         app.state = AppState::Help;
     } else if cmd.starts_with("/clear") {
         app.chat_messages.clear();
+        app.pending_context.clear();
         app.agent = None;
         app.loading = false;
         app.error = None;
@@ -840,6 +961,69 @@ pub(crate) fn handle_command_inline(app: &mut App, cmd: &str, previous_state: Ap
         }
         app.session_dirty = true;
         app.save_current_session();
+    } else if cmd.starts_with("/paste") {
+        let arg = cmd.trim_start_matches("/paste").trim();
+        if arg == "clear" {
+            let dropped = app.pending_context.len();
+            app.pending_context.clear();
+            app.chat_messages.push(ChatMessage::Assistant(format!(
+                "Dropped {} pasted block(s).",
+                dropped
+            )));
+        } else {
+            if let Err(err) = app.paste_clipboard(arg) {
+                app.chat_messages
+                    .push(ChatMessage::Error(format!("[paste] {}", err)));
+            }
+        }
+        if !app.chat_messages.is_empty() && !app.user_scrolled {
+            app.list_state.select(Some(app.chat_messages.len() - 1));
+        }
+    } else if cmd.starts_with("/tag") {
+        match app.tag_current_session(cmd.trim_start_matches("/tag")) {
+            Ok(msg) => app.chat_messages.push(ChatMessage::Assistant(msg)),
+            Err(err) => app
+                .chat_messages
+                .push(ChatMessage::Error(format!("[tag] {}", err))),
+        }
+        if !app.chat_messages.is_empty() && !app.user_scrolled {
+            app.list_state.select(Some(app.chat_messages.len() - 1));
+        }
+    } else if cmd == "/resume" {
+        match app.resume_branch_session() {
+            Ok(msg) => app.chat_messages.push(ChatMessage::Assistant(msg)),
+            Err(err) => app
+                .chat_messages
+                .push(ChatMessage::Error(format!("[resume] {}", err))),
+        }
+        if !app.chat_messages.is_empty() && !app.user_scrolled {
+            app.list_state.select(Some(app.chat_messages.len() - 1));
+        }
+    } else if cmd.starts_with("/speak") {
+        let arg = cmd.trim_start_matches("/speak").trim();
+        match arg {
+            "on" => app.speak_summaries = true,
+            "off" => app.speak_summaries = false,
+            "" => app.speak_summaries = !app.speak_summaries,
+            _ => {}
+        }
+        if !app.speak_summaries {
+            app.speaker.stop();
+        }
+        if matches!(arg, "" | "on" | "off") {
+            let _ = app.save_config();
+            app.chat_messages.push(ChatMessage::Assistant(format!(
+                "Spoken summaries are {}.",
+                if app.speak_summaries { "on" } else { "off" }
+            )));
+        } else {
+            app.chat_messages.push(ChatMessage::Error(
+                "[speak] usage: /speak [on|off]".to_string(),
+            ));
+        }
+        if !app.chat_messages.is_empty() && !app.user_scrolled {
+            app.list_state.select(Some(app.chat_messages.len() - 1));
+        }
     } else if cmd.starts_with("/theme") {
         app.previous_state = Some(previous_state);
         app.state = AppState::ThemeSelector;
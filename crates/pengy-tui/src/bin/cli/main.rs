@@ -1,19 +1,27 @@
 mod app;
+mod clipboard;
 mod command;
 mod constants;
+mod doctor;
 mod editor;
 mod handlers;
+mod session_filter;
+mod speech;
 mod syntax;
 mod theme;
 mod theme_select;
 mod ui;
+mod update;
+#[cfg(feature = "voice")]
+mod voice;
+mod watch;
 
 use app::{App, AppState};
 use command::{parse_agent_type, parse_cmd_args, run_cmd_mode};
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
-        MouseEventKind,
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event, KeyCode, KeyEventKind, KeyModifiers, MouseEventKind,
     },
     execute,
     terminal::{
@@ -22,6 +30,7 @@ use crossterm::{
     },
 };
 use handlers::{handle_state_key, scroll_chat_mouse};
+use pengy_agent::tool::approval::approval;
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::{
     error::Error,
@@ -32,6 +41,15 @@ use tokio::runtime::Runtime;
 use ui::ui;
 
 fn main() -> Result<(), Box<dyn Error>> {
+    if try_run_watch_mode()? {
+        return Ok(());
+    }
+    if try_run_self_update() {
+        return Ok(());
+    }
+    if try_run_doctor() {
+        return Ok(());
+    }
     if try_run_cmd_mode()? {
         return Ok(());
     }
@@ -45,11 +63,71 @@ fn main() -> Result<(), Box<dyn Error>> {
     result
 }
 
+fn try_run_watch_mode() -> Result<bool, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) != Some("watch") {
+        return Ok(false);
+    }
+    match watch::parse_watch_args(&args[2..]) {
+        Ok(options) => {
+            let rt = Runtime::new()?;
+            watch::run_watch_mode(options, &rt)?;
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            watch::print_watch_usage();
+            std::process::exit(1);
+        }
+    }
+    Ok(true)
+}
+
+fn try_run_self_update() -> bool {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) != Some("self-update") {
+        return false;
+    }
+    if args.iter().skip(2).any(|a| a == "--help" || a == "-h") {
+        update::print_self_update_usage();
+        return true;
+    }
+    if let Err(e) = update::run_self_update(&args[2..]) {
+        eprintln!("Error: {}", e);
+        if e.starts_with("Unknown self-update argument") {
+            update::print_self_update_usage();
+        }
+        std::process::exit(1);
+    }
+    true
+}
+
+fn try_run_doctor() -> bool {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) != Some("doctor") {
+        return false;
+    }
+    if args.iter().skip(2).any(|a| a == "--help" || a == "-h") {
+        doctor::print_doctor_usage();
+        return true;
+    }
+    if let Some(arg) = args.get(2) {
+        eprintln!("Error: Unknown doctor argument: {}", arg);
+        doctor::print_doctor_usage();
+        std::process::exit(1);
+    }
+    if !doctor::run_doctor() {
+        std::process::exit(1);
+    }
+    true
+}
+
 fn try_run_cmd_mode() -> Result<bool, Box<dyn Error>> {
     if let Some((prompt, agent_str, model, provider, api_key, base_url)) = parse_cmd_args() {
         let rt = Runtime::new()?;
         match parse_agent_type(&agent_str) {
             Ok(agent_type) => {
+                approval::set_handler(approval::stdin_handler());
+                approval::set_selection_handler(approval::stdin_selection_handler());
                 rt.block_on(run_cmd_mode(
                     prompt, agent_type, model, provider, api_key, base_url,
                 ))?;
@@ -98,6 +176,7 @@ fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>, Box<dyn Error>
         stdout,
         EnterAlternateScreen,
         EnableMouseCapture,
+        EnableFocusChange,
         Clear(ClearType::All)
     )?;
     let backend = CrosstermBackend::new(stdout);
@@ -111,7 +190,8 @@ fn cleanup_terminal(
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableFocusChange
     )?;
     terminal.show_cursor()?;
     Ok(())
@@ -124,6 +204,9 @@ fn run_tui(
 ) -> Result<(), Box<dyn Error>> {
     loop {
         app.process_events();
+        if app.poll_diagnosis() {
+            rt.block_on(app.send_message())?;
+        }
         terminal.draw(|f| ui(f, app))?;
 
         if event::poll(Duration::from_millis(16))? {
@@ -138,6 +221,8 @@ fn run_tui(
                         && key.code == KeyCode::Char('c')
                     {
                         if app.loading {
+                            app.resolve_approval(false);
+                            app.resolve_selection(false);
                             app.loading = false;
                             app.error = Some("Stopped by user (Ctrl+C)".to_string());
                             continue;
@@ -162,6 +247,14 @@ fn run_tui(
                         continue;
                     }
 
+                    #[cfg(feature = "voice")]
+                    if key.modifiers.contains(KeyModifiers::CONTROL)
+                        && key.code == KeyCode::Char('t')
+                    {
+                        app.toggle_voice();
+                        continue;
+                    }
+
                     let should_quit = handle_state_key(app, key.code, rt)?;
 
                     if should_quit {
@@ -181,6 +274,8 @@ fn run_tui(
                     }
                     _ => {}
                 },
+                Event::FocusLost => app.terminal_focused = false,
+                Event::FocusGained => app.terminal_focused = true,
                 _ => {}
             }
         }
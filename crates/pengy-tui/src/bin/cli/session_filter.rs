@@ -0,0 +1,204 @@
+//! Session metadata (tags, workspace, git branch, creation time) and the
+//! query language used to filter the session selector.
+//!
+//! A query is a list of whitespace-separated terms that must all match:
+//! `#bug` or `tag:bug` (tag), `ws:api` or `workspace:api` (workspace path
+//! contains), `branch:feat` (git branch contains), `date:2026-10`,
+//! `after:2026-10-01` and `before:2026-10-15` (creation date, UTC, both
+//! bounds inclusive; a year, month or day), and any other word, which must
+//! appear in the title or a tag.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECS_PER_DAY: u64 = 86_400;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SessionMeta {
+    pub tags: Vec<String>,
+    pub workspace: String,
+    /// Git branch checked out when the session was created.
+    pub branch: Option<String>,
+    /// Creation time in seconds since the Unix epoch.
+    pub created: u64,
+    /// Messages in the session when it was last saved.
+    pub messages: usize,
+}
+
+impl SessionMeta {
+    pub(crate) fn now(workspace: String, branch: Option<String>) -> Self {
+        SessionMeta {
+            tags: Vec::new(),
+            workspace,
+            branch,
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            messages: 0,
+        }
+    }
+}
+
+/// Lowercase a tag and strip a leading `#`; tags are limited to
+/// letters, digits and `-_./`.
+pub(crate) fn normalize_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim().trim_start_matches('#').to_lowercase();
+    let valid = !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+    valid.then_some(tag)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SessionQuery {
+    words: Vec<String>,
+    tags: Vec<String>,
+    workspaces: Vec<String>,
+    branches: Vec<String>,
+    /// Inclusive start and exclusive end of the creation-time window.
+    from: Option<u64>,
+    until: Option<u64>,
+}
+
+impl SessionQuery {
+    pub(crate) fn parse(query: &str) -> Self {
+        let mut parsed = SessionQuery::default();
+        for term in query.split_whitespace() {
+            let lower = term.to_lowercase();
+            let (key, value) = lower.split_once(':').unwrap_or(("", &lower));
+            match key {
+                "tag" => parsed.tags.extend(normalize_tag(value)),
+                "ws" | "workspace" if !value.is_empty() => {
+                    parsed.workspaces.push(value.to_string())
+                }
+                "branch" if !value.is_empty() => parsed.branches.push(value.to_string()),
+                "date" | "on" if date_range(value).is_some() => {
+                    let (start, end) = date_range(value).unwrap();
+                    parsed.from = Some(parsed.from.map_or(start, |f| f.max(start)));
+                    parsed.until = Some(parsed.until.map_or(end, |u| u.min(end)));
+                }
+                "after" | "since" if date_range(value).is_some() => {
+                    let (start, _) = date_range(value).unwrap();
+                    parsed.from = Some(parsed.from.map_or(start, |f| f.max(start)));
+                }
+                "before" | "until" if date_range(value).is_some() => {
+                    let (_, end) = date_range(value).unwrap();
+                    parsed.until = Some(parsed.until.map_or(end, |u| u.min(end)));
+                }
+                _ if lower.starts_with('#') => parsed.tags.extend(normalize_tag(&lower)),
+                _ => parsed.words.push(lower),
+            }
+        }
+        parsed
+    }
+
+    pub(crate) fn matches(&self, title: &str, meta: &SessionMeta) -> bool {
+        let title = title.to_lowercase();
+        let workspace = meta.workspace.to_lowercase();
+        let branch = meta.branch.as_deref().unwrap_or_default().to_lowercase();
+        self.tags.iter().all(|tag| meta.tags.contains(tag))
+            && self.workspaces.iter().all(|ws| workspace.contains(ws))
+            && self.branches.iter().all(|b| branch.contains(b))
+            && self.from.is_none_or(|from| meta.created >= from)
+            && self.until.is_none_or(|until| meta.created < until)
+            && self
+                .words
+                .iter()
+                .all(|word| title.contains(word) || meta.tags.iter().any(|t| t.contains(word)))
+    }
+}
+
+/// Start and end (exclusive) of a `YYYY`, `YYYY-MM` or `YYYY-MM-DD` period
+/// in UTC epoch seconds.
+fn date_range(value: &str) -> Option<(u64, u64)> {
+    let parts: Vec<&str> = value.split('-').collect();
+    let year: i64 = parts.first()?.parse().ok().filter(|y| *y >= 1970)?;
+    let month: Option<u32> = match parts.get(1) {
+        Some(m) => Some(m.parse().ok().filter(|m| (1..=12).contains(m))?),
+        None => None,
+    };
+    let day: Option<u32> = match parts.get(2) {
+        Some(d) => Some(d.parse().ok().filter(|d| (1..=31).contains(d))?),
+        None => None,
+    };
+    if parts.len() > 3 || (day.is_some() && month.is_none()) {
+        return None;
+    }
+
+    let (start, end) = match (month, day) {
+        (Some(m), Some(d)) => {
+            let start = days_from_civil(year, m, d);
+            (start, start + 1)
+        }
+        (Some(m), None) => {
+            let next = if m == 12 {
+                days_from_civil(year + 1, 1, 1)
+            } else {
+                days_from_civil(year, m + 1, 1)
+            };
+            (days_from_civil(year, m, 1), next)
+        }
+        _ => (days_from_civil(year, 1, 1), days_from_civil(year + 1, 1, 1)),
+    };
+    Some((start as u64 * SECS_PER_DAY, end as u64 * SECS_PER_DAY))
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's
+/// algorithm).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// `YYYY-MM-DD` (UTC) for epoch seconds.
+pub(crate) fn format_date(secs: u64) -> String {
+    let z = (secs / SECS_PER_DAY) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_query_matches_tags_words_workspace_and_dates() {
+        let meta = SessionMeta {
+            tags: vec!["bug-123".to_string(), "refactor".to_string()],
+            workspace: "/home/dev/api-server".to_string(),
+            branch: Some("fix/login-timeout".to_string()),
+            // 2026-10-16T12:00:00Z
+            created: 1_792_152_000,
+            messages: 4,
+        };
+        assert_eq!(format_date(meta.created), "2026-10-16");
+
+        let matches = |q: &str| SessionQuery::parse(q).matches("Fix login timeout", &meta);
+        assert!(matches(""));
+        assert!(matches("#Refactor login"));
+        assert!(matches("tag:bug-123 ws:api branch:login"));
+        assert!(!matches("branch:main"));
+        assert!(matches("bug"));
+        assert!(matches("date:2026-10 after:2026-10-16 before:2026-10-16"));
+        assert!(!matches("#perf"));
+        assert!(!matches("ws:frontend"));
+        assert!(!matches("after:2026-10-17"));
+        assert!(!matches("before:2026-10-15"));
+        assert!(!matches("date:2025"));
+        // Malformed dates are searched as plain words
+        assert!(!matches("date:2026-13"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Spoken summaries of finished runs (`/speak`). When enabled, the first
+//! sentence of each final response is read aloud with the system's speech
+//! synthesizer: `say` on macOS, System.Speech via PowerShell on Windows and
+//! spd-say, espeak-ng or espeak elsewhere. `tts_command` in the config
+//! replaces these; the sentence is passed as its last argument.
+//!
+//! A summary is only spoken when the user is likely to be looking elsewhere:
+//! the terminal has lost focus (for terminals that report focus changes) or
+//! the run took longer than [`SPEAK_AFTER`].
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+const MAX_SUMMARY_CHARS: usize = 200;
+/// Runs at least this long are summarized even while the terminal has focus.
+pub(crate) const SPEAK_AFTER: Duration = Duration::from_secs(30);
+
+static LINK: Lazy<Regex> = Lazy::new(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap());
+static LIST_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:[-*+]|\d+[.)])\s+").unwrap());
+static SENTENCE_END: Lazy<Regex> = Lazy::new(|| Regex::new(r"[.!?](?:\s|$)").unwrap());
+
+/// Whether a run that took `elapsed` should be summarized aloud.
+pub(crate) fn should_speak(terminal_focused: bool, elapsed: Duration) -> bool {
+    !terminal_focused || elapsed >= SPEAK_AFTER
+}
+
+/// The first sentence of a markdown response as plain text, skipping code
+/// blocks, headings and tables.
+pub(crate) fn summary_sentence(response: &str) -> Option<String> {
+    let mut in_fence = false;
+    let mut prose = Vec::new();
+    for line in response.lines() {
+        let line = line.trim();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || line.starts_with('#') || line.starts_with('|') || line.starts_with("---") {
+            if !prose.is_empty() {
+                break;
+            }
+            continue;
+        }
+        if line.is_empty() {
+            if !prose.is_empty() {
+                break;
+            }
+            continue;
+        }
+        let line = LIST_MARKER.replace(line, "");
+        let line = LINK.replace_all(&line, "$1");
+        prose.push(line.replace(['*', '`'], "").replace("__", ""));
+    }
+
+    let paragraph = prose.join(" ");
+    let paragraph = paragraph.trim();
+    if paragraph.is_empty() {
+        return None;
+    }
+    let sentence = match SENTENCE_END.find(paragraph) {
+        Some(end) => &paragraph[..end.start() + 1],
+        None => paragraph,
+    };
+    if sentence.chars().count() <= MAX_SUMMARY_CHARS {
+        return Some(sentence.to_string());
+    }
+    let cut: String = sentence.chars().take(MAX_SUMMARY_CHARS).collect();
+    let cut = cut.rsplit_once(' ').map(|(head, _)| head).unwrap_or(&cut);
+    Some(format!("{}...", cut.trim_end_matches([',', ';', ':'])))
+}
+
+/// Plays one utterance at a time; starting a new one stops the previous.
+#[derive(Default)]
+pub(crate) struct Speaker {
+    current: Option<Child>,
+}
+
+impl Speaker {
+    pub(crate) fn speak(&mut self, text: &str, command: Option<&str>) -> Result<(), String> {
+        self.stop();
+        let candidates: Vec<(String, Vec<String>)> = match command.map(str::trim) {
+            Some(custom) if !custom.is_empty() => {
+                let mut parts = custom.split_whitespace().map(str::to_string);
+                let program = parts.next().unwrap_or_default();
+                let mut args: Vec<String> = parts.collect();
+                args.push(text.to_string());
+                vec![(program, args)]
+            }
+            _ if cfg!(target_os = "macos") => vec![("say".to_string(), vec![text.to_string()])],
+            _ if cfg!(target_os = "windows") => vec![(
+                "powershell".to_string(),
+                vec![
+                    "-NoProfile".to_string(),
+                    "-NonInteractive".to_string(),
+                    "-Command".to_string(),
+                    "Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak($env:PENGY_TTS_TEXT)".to_string(),
+                ],
+            )],
+            _ => ["spd-say", "espeak-ng", "espeak"]
+                .iter()
+                .map(|program| (program.to_string(), vec![text.to_string()]))
+                .collect(),
+        };
+
+        let mut tried = Vec::new();
+        for (program, args) in candidates {
+            let spawned = Command::new(&program)
+                .args(&args)
+                .env("PENGY_TTS_TEXT", text)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+            match spawned {
+                Ok(child) => {
+                    self.current = Some(child);
+                    return Ok(());
+                }
+                Err(_) => tried.push(program),
+            }
+        }
+        Err(format!(
+            "no speech synthesizer found (tried {}); set tts_command in the config",
+            tried.join(", ")
+        ))
+    }
+
+    pub(crate) fn stop(&mut self) {
+        if let Some(mut child) = self.current.take()
+            && matches!(child.try_wait(), Ok(None))
+        {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_is_first_prose_sentence_without_markdown() {
+        let response = "## Summary\n\n- Fixed the **off-by-one** in `parse_range` (see [ranges](src/range.rs)). Tests pass now.\n\n```rust\nfn main() {}\n```";
+        assert_eq!(
+            summary_sentence(response).as_deref(),
+            Some("Fixed the off-by-one in parse_range (see ranges).")
+        );
+        assert_eq!(summary_sentence("```\ncode only\n```"), None);
+
+        let long = format!("{} end", "word ".repeat(80));
+        let summary = summary_sentence(&long).unwrap();
+        assert!(summary.ends_with("...") && summary.chars().count() <= MAX_SUMMARY_CHARS + 3);
+    }
+
+    #[test]
+    fn speaks_only_when_unfocused_or_slow() {
+        assert!(!should_speak(true, Duration::from_secs(5)));
+        assert!(should_speak(false, Duration::from_secs(5)));
+        assert!(should_speak(true, SPEAK_AFTER));
+    }
+}
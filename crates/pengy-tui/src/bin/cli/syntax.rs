@@ -1,19 +1,22 @@
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Span;
+#[cfg(feature = "syntax-highlight")]
 use std::sync::OnceLock;
+#[cfg(feature = "syntax-highlight")]
 use tree_sitter::{Language, Parser, Query, QueryCursor};
 
-// Language grammars - lazy loaded using OnceLock for thread safety
-static RUST_LANG: OnceLock<Language> = OnceLock::new();
-static PYTHON_LANG: OnceLock<Language> = OnceLock::new();
-static JAVASCRIPT_LANG: OnceLock<Language> = OnceLock::new();
-static TYPESCRIPT_LANG: OnceLock<Language> = OnceLock::new();
-static GO_LANG: OnceLock<Language> = OnceLock::new();
-static JAVA_LANG: OnceLock<Language> = OnceLock::new();
-static C_LANG: OnceLock<Language> = OnceLock::new();
-static CPP_LANG: OnceLock<Language> = OnceLock::new();
-
+#[cfg(feature = "syntax-highlight")]
 fn get_language(lang: &str) -> Option<&'static Language> {
+    // Language grammars - lazy loaded using OnceLock for thread safety
+    static RUST_LANG: OnceLock<Language> = OnceLock::new();
+    static PYTHON_LANG: OnceLock<Language> = OnceLock::new();
+    static JAVASCRIPT_LANG: OnceLock<Language> = OnceLock::new();
+    static TYPESCRIPT_LANG: OnceLock<Language> = OnceLock::new();
+    static GO_LANG: OnceLock<Language> = OnceLock::new();
+    static JAVA_LANG: OnceLock<Language> = OnceLock::new();
+    static C_LANG: OnceLock<Language> = OnceLock::new();
+    static CPP_LANG: OnceLock<Language> = OnceLock::new();
+
     Some(match lang.to_lowercase().as_str() {
         "rust" | "rs" => RUST_LANG.get_or_init(|| tree_sitter_rust::language()),
         "python" | "py" => PYTHON_LANG.get_or_init(|| tree_sitter_python::language()),
@@ -27,6 +30,7 @@ fn get_language(lang: &str) -> Option<&'static Language> {
     })
 }
 
+#[cfg(feature = "syntax-highlight")]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TokenKind {
     Comment,
@@ -43,6 +47,7 @@ pub enum TokenKind {
     Normal,
 }
 
+#[cfg(feature = "syntax-highlight")]
 fn map_capture_to_kind(capture_name: &str) -> TokenKind {
     match capture_name {
         "comment" => TokenKind::Comment,
@@ -59,6 +64,7 @@ fn map_capture_to_kind(capture_name: &str) -> TokenKind {
     }
 }
 
+#[cfg(feature = "syntax-highlight")]
 fn get_style_for_kind(kind: TokenKind, accent: Color, bg: Color) -> Style {
     let base = Style::default().bg(bg);
     match kind {
@@ -77,6 +83,7 @@ fn get_style_for_kind(kind: TokenKind, accent: Color, bg: Color) -> Style {
     }
 }
 
+#[cfg(feature = "syntax-highlight")]
 pub fn highlight_line_with_tree_sitter(
     line: &str,
     lang: &str,
@@ -287,6 +294,18 @@ pub fn highlight_line_with_tree_sitter(
     spans
 }
 
+/// Without the `syntax-highlight` feature every line goes through the
+/// keyword-based highlighter.
+#[cfg(not(feature = "syntax-highlight"))]
+pub fn highlight_line_with_tree_sitter(
+    line: &str,
+    lang: &str,
+    accent: Color,
+    bg: Color,
+) -> Vec<Span<'static>> {
+    apply_basic_highlighting(line, lang, accent, bg)
+}
+
 // Fallback basic highlighting when tree-sitter fails
 fn apply_basic_highlighting(line: &str, lang: &str, accent: Color, bg: Color) -> Vec<Span<'static>> {
     use std::collections::HashSet;
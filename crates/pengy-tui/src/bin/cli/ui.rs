@@ -1,4 +1,4 @@
-use crate::app::{AgentType, App, AppState, ChatMessage, ModelOption, ToolStatus};
+use crate::app::{AgentType, App, AppState, ChatMessage, ModelOption, SelectionRow, ToolStatus};
 use crate::constants::{DEFAULT_BASE_URL, MAX_TOKENS, VERSION};
 // Theme definitions are accessed via app.current_theme()
 use crate::syntax::highlight_line_with_tree_sitter;
@@ -403,6 +403,7 @@ pub(crate) fn ui(f: &mut Frame, app: &mut App) {
                 AppState::CustomModel => render_custom_model(f, app, main_chunks[1]),
                 AppState::BaseUrlSelector => render_baseurl_selector(f, app, main_chunks[1]),
                 AppState::ThemeSelector => render_theme_selector(f, app, main_chunks[1]),
+                AppState::TrashBrowser => render_trash_browser(f, app, main_chunks[1]),
                 AppState::SessionSelector | AppState::Chat | AppState::Welcome | AppState::Editor => unreachable!(),
             }
         }
@@ -432,9 +433,138 @@ pub(crate) fn ui(f: &mut Frame, app: &mut App) {
         }
     }
 
+    if app.pending_selection.is_some() {
+        render_selection_picker(f, app, layout[1]);
+    }
+    if app.pending_approval.is_some() {
+        render_approval_prompt(f, app, layout[1]);
+    }
+
     render_status_bar(f, app, layout[3]);
 }
 
+fn render_selection_picker(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.current_theme();
+    let Some(pending) = app.pending_selection.as_mut() else {
+        return;
+    };
+    let rect = centered_rect(90, 90, area);
+    f.render_widget(Clear, rect);
+    let accepted = pending.accepted.iter().filter(|a| **a).count();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "{} ({}/{} selected)",
+            pending.request.title,
+            accepted,
+            pending.accepted.len()
+        ))
+        .title_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(rect);
+    f.render_widget(block, rect);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(4),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let items: Vec<ListItem> = pending
+        .rows
+        .iter()
+        .map(|row| match *row {
+            SelectionRow::Group { start, end } => {
+                let on = pending.accepted[start..end].iter().filter(|a| **a).count();
+                let mark = if on == end - start {
+                    "[x]"
+                } else if on == 0 {
+                    "[ ]"
+                } else {
+                    "[-]"
+                };
+                ListItem::new(Line::from(Span::styled(
+                    format!(
+                        "{} {} ({}/{})",
+                        mark,
+                        pending.request.items[start].group,
+                        on,
+                        end - start
+                    ),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )))
+            }
+            SelectionRow::Item(i) => {
+                let mark = if pending.accepted[i] { "[x]" } else { "[ ]" };
+                ListItem::new(format!("    {} {}", mark, pending.request.items[i].label))
+            }
+        })
+        .collect();
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_stateful_widget(list, layout[0], &mut pending.list_state);
+
+    let detail = pending
+        .current_item()
+        .map(|i| pending.request.items[i].detail.clone())
+        .unwrap_or_default();
+    let detail_lines: Vec<Line> = detail
+        .lines()
+        .map(|line| {
+            let color = if line.starts_with('+') {
+                Color::Green
+            } else if line.starts_with('-') {
+                Color::Red
+            } else {
+                Color::Gray
+            };
+            Line::from(Span::styled(line.to_string(), Style::default().fg(color)))
+        })
+        .collect();
+    let preview = Paragraph::new(detail_lines)
+        .block(Block::default().borders(Borders::TOP).title("Preview"))
+        .wrap(Wrap { trim: false });
+    f.render_widget(preview, layout[1]);
+
+    let help = Paragraph::new("Space toggle  a toggle all  Enter apply  Esc cancel")
+        .style(Style::default().fg(Color::DarkGray));
+    f.render_widget(help, layout[2]);
+}
+
+fn render_approval_prompt(f: &mut Frame, app: &App, area: Rect) {
+    let Some(pending) = app.pending_approval.as_ref() else {
+        return;
+    };
+    let theme = app.current_theme();
+    let rect = centered_rect(60, 30, area);
+    f.render_widget(Clear, rect);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Approval Required")
+        .title_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .style(Style::default().bg(theme.bg));
+    let text = format!(
+        "{} wants to {}.\n\n[y/Enter] Allow    [n/Esc] Deny",
+        pending.request.tool, pending.request.action
+    );
+    let p = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+    f.render_widget(p, rect);
+}
+
 fn render_tool_call_card(
     id: &str,
     name: &str,
@@ -1630,7 +1760,7 @@ fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
         .unwrap_or(".")
         .to_string();
 
-    let status_line = Line::from(vec![
+    let mut status_line = Line::from(vec![
         Span::styled(" ", Style::default()),
         Span::styled(cwd, Style::default().fg(Color::Rgb(140, 140, 160))),
         Span::styled(" │ ", Style::default().fg(Color::Rgb(80, 80, 100))),
@@ -1647,6 +1777,51 @@ fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         ),
     ]);
+    if !app.pending_context.is_empty() {
+        status_line.spans.extend([
+            Span::styled(" │ ", Style::default().fg(Color::Rgb(80, 80, 100))),
+            Span::styled(
+                format!("Pasted: {} block(s)", app.pending_context.len()),
+                Style::default().fg(Color::Rgb(120, 170, 220)),
+            ),
+        ]);
+    }
+
+    if let (Some(branch), Some(title)) = (
+        app.observed_branch.as_ref(),
+        app.branch_session_offer.and_then(|i| app.sessions.get(i)),
+    ) {
+        status_line.spans.extend([
+            Span::styled(" │ ", Style::default().fg(Color::Rgb(80, 80, 100))),
+            Span::styled(
+                format!("⎇ {}: /resume \"{}\"", branch, title),
+                Style::default().fg(Color::Rgb(120, 170, 220)),
+            ),
+        ]);
+    }
+
+    if let Some(version) = &app.available_update {
+        status_line.spans.extend([
+            Span::styled(" │ ", Style::default().fg(Color::Rgb(80, 80, 100))),
+            Span::styled(
+                format!("v{} available: pengy self-update", version),
+                Style::default().fg(Color::Rgb(200, 160, 80)),
+            ),
+        ]);
+    }
+
+    #[cfg(feature = "voice")]
+    if let Some(voice) = app.voice.status() {
+        status_line.spans.extend([
+            Span::styled(" │ ", Style::default().fg(Color::Rgb(80, 80, 100))),
+            Span::styled(
+                voice,
+                Style::default()
+                    .fg(Color::Rgb(230, 90, 90))
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+    }
 
     let status = Paragraph::new(vec![status_line, Line::from("")])
         .style(Style::default().bg(theme.status_bg));
@@ -1808,7 +1983,19 @@ fn render_welcome(f: &mut Frame, app: &App, area: Rect) {
         .style(Style::default().fg(Color::White));
     f.render_widget(logo_paragraph, chunks[0]);
 
-    let info = vec![Line::from("Type your prompt to start chatting.")];
+    let mut info = vec![Line::from("Type your prompt to start chatting.")];
+    if let (Some(branch), Some(title)) = (
+        app.observed_branch.as_ref(),
+        app.branch_session_offer.and_then(|i| app.sessions.get(i)),
+    ) {
+        info.push(Line::from(Span::styled(
+            format!(
+                "Last session on {}: \"{}\" (type /resume to continue it)",
+                branch, title
+            ),
+            Style::default().fg(Color::Rgb(120, 170, 220)),
+        )));
+    }
     let info_block = Block::default()
         .borders(Borders::NONE)
         .title("Getting Started");
@@ -2270,18 +2457,144 @@ fn render_settings(f: &mut Frame, app: &mut App, area: Rect) {
 
 fn render_session_selector(f: &mut Frame, app: &mut App, area: Rect) {
     f.render_widget(Clear, area);
-    let rect = centered_rect(60, 60, area);
+    let rect = centered_rect(70, 70, area);
     f.render_widget(Clear, rect);
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Sessions (hjkl/↑↓)");
+    let inner = block.inner(rect);
+    f.render_widget(block, rect);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Filter
+            Constraint::Min(3),    // List
+            Constraint::Length(1), // Hint
+        ])
+        .split(inner);
+
+    let filter_block = Block::default()
+        .borders(Borders::ALL)
+        .title(if app.session_filter_focused {
+            "Filter (active)"
+        } else {
+            "Filter"
+        })
+        .title_style(if app.session_filter_focused {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::Gray)
+        });
+    let (filter_text, filter_style) = if app.session_filter.is_empty() {
+        (
+            "title words, #tag, ws:<path>, branch:<name>, date:2026-10, after:/before:<date>"
+                .to_string(),
+            Style::default().fg(Color::DarkGray),
+        )
+    } else {
+        (
+            app.session_filter.clone(),
+            Style::default().fg(Color::White),
+        )
+    };
+    f.render_widget(
+        Paragraph::new(filter_text)
+            .block(filter_block)
+            .style(filter_style),
+        layout[0],
+    );
+
+    let filtered = app.filtered_sessions();
+    if filtered.is_empty() {
+        let empty =
+            Paragraph::new("No sessions match the filter").style(Style::default().fg(Color::Gray));
+        f.render_widget(empty, layout[1]);
+    } else {
+        let items: Vec<ListItem> = filtered
+            .iter()
+            .map(|&i| {
+                let marker = if i == app.current_session { "●" } else { " " };
+                let mut spans = vec![Span::raw(format!("{} {}", marker, app.sessions[i]))];
+                if let Some(meta) = app.session_meta.get(i) {
+                    if !meta.tags.is_empty() {
+                        let tags: Vec<String> =
+                            meta.tags.iter().map(|t| format!("#{}", t)).collect();
+                        spans.push(Span::styled(
+                            format!("  {}", tags.join(" ")),
+                            Style::default().fg(Color::Rgb(120, 170, 220)),
+                        ));
+                    }
+                    if let Some(branch) = &meta.branch {
+                        spans.push(Span::styled(
+                            format!("  ⎇ {}", branch),
+                            Style::default().fg(Color::Rgb(140, 140, 160)),
+                        ));
+                    }
+                    if meta.created > 0 {
+                        spans.push(Span::styled(
+                            format!("  {}", crate::session_filter::format_date(meta.created)),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_stateful_widget(list, layout[1], &mut app.session_list_state);
+    }
+
+    let hint = Paragraph::new(format!(
+        "Enter: open  •  Tab: filter  •  Esc: back  •  {}/{} sessions",
+        filtered.len(),
+        app.sessions.len()
+    ))
+    .style(Style::default().fg(Color::Gray));
+    f.render_widget(hint, layout[2]);
+}
+
+fn render_trash_browser(f: &mut Frame, app: &mut App, area: Rect) {
+    f.render_widget(Clear, area);
+    let rect = centered_rect(90, 80, area);
+    f.render_widget(Clear, rect);
+    let scope = if app.trash_show_all {
+        "all sessions"
+    } else {
+        "this session"
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Trash: {} (Enter restore, Tab scope)", scope));
+
+    if app.trash_entries.is_empty() {
+        let p = Paragraph::new("Nothing in the trash.")
+            .block(block)
+            .wrap(Wrap { trim: true });
+        f.render_widget(p, rect);
+        return;
+    }
+
     let items: Vec<ListItem> = app
-        .sessions
+        .trash_entries
         .iter()
-        .enumerate()
-        .map(|(i, s)| {
-            let marker = if i == app.current_session { "●" } else { " " };
-            ListItem::new(format!("{} {}", marker, s))
+        .map(|e| {
+            let name = e
+                .original_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| e.original_path.display().to_string());
+            ListItem::new(vec![
+                Line::from(format!("{} [{}]", name, e.reason)),
+                Line::from(Span::styled(
+                    format!("  {}", e.original_path.display()),
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ])
         })
         .collect();
     let list = List::new(items).block(block).highlight_style(
@@ -2289,7 +2602,7 @@ fn render_session_selector(f: &mut Frame, app: &mut App, area: Rect) {
             .fg(Color::Yellow)
             .add_modifier(Modifier::BOLD),
     );
-    f.render_stateful_widget(list, rect, &mut app.session_list_state);
+    f.render_stateful_widget(list, rect, &mut app.trash_list_state);
 }
 
 fn render_help(f: &mut Frame, _app: &App, area: Rect) {
@@ -2297,7 +2610,15 @@ fn render_help(f: &mut Frame, _app: &App, area: Rect) {
     let rect = centered_rect(60, 60, area);
     f.render_widget(Clear, rect);
     let block = Block::default().borders(Borders::ALL).title("Help");
-    let text = "Available Commands:\n\n/models - Select Model\n/agents - Select Agent\n/settings - Configure API key / model / base URL\n/baseurl - Select provider base URL (Mistral, DeepSeek, OpenRouter, etc.)\n/help - Show this help screen\n/clear - Clear conversation and reset agent\n/sandbox - Enable sandbox mode (auto-commit every run; merge with /save)\n/save - Merge sandbox branch back to the base branch and switch back\n\nNavigation:\nUse Arrows to navigate lists.\nTab to switch between fields/agents.\nEnter to select.\nEsc to go back.\n\nTip: Type '/' in the input to see all available commands with autocomplete.";
+    let voice_hint = if cfg!(feature = "voice") {
+        "Ctrl+T to start/stop voice input.\n"
+    } else {
+        ""
+    };
+    let text = format!(
+        "Available Commands:\n\n/models - Select Model\n/agents - Select Agent\n/settings - Configure API key / model / base URL\n/baseurl - Select provider base URL (Mistral, DeepSeek, OpenRouter, etc.)\n/help - Show this help screen\n/clear - Clear conversation and reset agent\n/sandbox - Enable sandbox mode (auto-commit every run; merge with /save)\n/save - Merge sandbox branch back to the base branch and switch back\n/trash - Browse and restore files deleted or overwritten by tools\n/paste [label] - Attach clipboard text to your next message (/paste clear drops it)\n/diagnose [error] - Root-cause an error or stack trace (inline, pasted or from the clipboard)\n/sessions [filter] - Switch session, optionally filtered (#tag, ws:path, branch:name, date:2026-10, after:/before:date)\n/tag <tags> - Tag the current session (/tag -name removes, /tag lists)\n/resume - Resume the last session created on the current git branch\n/speak [on|off] - Read a one-sentence summary of each finished run aloud\n\nNavigation:\nUse Arrows to navigate lists.\nTab to switch between fields/agents.\nEnter to select.\nEsc to go back.\n{}\nTip: Type '/' in the input to see all available commands with autocomplete.",
+        voice_hint
+    );
     let p = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
     f.render_widget(p, rect);
 }
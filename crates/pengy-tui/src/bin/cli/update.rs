@@ -0,0 +1,415 @@
+//! `pengy self-update` and the startup "new version available" check.
+//!
+//! Releases are read from GitHub. Each release carries one binary per
+//! platform, named `pengy-<os>-<arch>` (`.exe` on Windows, e.g.
+//! `pengy-linux-x86_64`), a `SHA256SUMS` file listing their checksums in
+//! `sha256sum` format and `SHA256SUMS.minisig`, a minisign signature of that
+//! file whose trusted comment is the release version (`minisign -S -m
+//! SHA256SUMS -t 1.2.3`), so an old signed file cannot be replayed under a
+//! newer tag. The signature is checked against the public key compiled in
+//! from `PENGY_UPDATE_PUBLIC_KEY`; builds without one cannot self-update. A
+//! download is only installed if the signature is valid and its checksum
+//! matches; it is staged next to the running executable and renamed over
+//! it, so an interrupted update never leaves a partial binary behind.
+
+use crate::app::App;
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/JasonHonKL/PengyAgent/releases/latest";
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+const SIGNATURE_ASSET: &str = "SHA256SUMS.minisig";
+/// Minisign public key (base64) that release checksums are signed with.
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("PENGY_UPDATE_PUBLIC_KEY");
+const NO_SIGNING_KEY: &str = "this build has no update signing key; download the release manually";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The startup check hits the GitHub API at most this often.
+const CHECK_INTERVAL_SECS: u64 = 24 * 60 * 60;
+/// Kept next to the config file in `$HOME`.
+const CHECK_CACHE_FILE: &str = ".pengy_update_check.json";
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CheckCache {
+    checked: u64,
+    latest: String,
+}
+
+/// Release asset holding the binary for this platform.
+fn platform_asset_name() -> String {
+    let suffix = if cfg!(windows) { ".exe" } else { "" };
+    format!("pengy-{}-{}{}", env::consts::OS, env::consts::ARCH, suffix)
+}
+
+/// Numeric components of a version such as `v1.2.3` or `1.2.3-beta.1`
+/// (pre-release suffixes are ignored).
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let core = version.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next()?;
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Checksum listed for `asset` in a `sha256sum`-style file.
+fn expected_checksum(checksums: &str, asset: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (name == asset && hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| hash.to_lowercase())
+    })
+}
+
+/// Check the minisign `signature` of `checksums` against `public_key`, and
+/// that its trusted comment names `version`.
+fn verify_checksums(
+    checksums: &[u8],
+    signature: &str,
+    public_key: Option<&str>,
+    version: &str,
+) -> Result<(), String> {
+    let public_key = public_key.ok_or(NO_SIGNING_KEY)?;
+    let public_key = PublicKey::from_base64(public_key.trim())
+        .map_err(|e| format!("invalid update signing key: {}", e))?;
+    let signature =
+        Signature::decode(signature).map_err(|e| format!("invalid {}: {}", SIGNATURE_ASSET, e))?;
+    public_key
+        .verify(checksums, &signature, false)
+        .map_err(|e| format!("{} signature is not valid: {}", CHECKSUMS_ASSET, e))?;
+    let signed_version = signature.trusted_comment().trim().trim_start_matches('v');
+    if signed_version != version {
+        return Err(format!(
+            "{} is signed for version {:?}, not {}; refusing to install it",
+            CHECKSUMS_ASSET, signed_version, version
+        ));
+    }
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn block_on<F: std::future::Future>(future: F) -> Result<F::Output, String> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to create async runtime: {}", e))?;
+    Ok(rt.block_on(future))
+}
+
+fn client(timeout: Duration) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .user_agent(format!("pengy/{}", CURRENT_VERSION))
+        .timeout(timeout)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+async fn fetch_latest_release(timeout: Duration) -> Result<Release, String> {
+    let response = client(timeout)?
+        .get(RELEASES_URL)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach GitHub: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("GitHub returned {}", response.status()));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Unexpected release data: {}", e))
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, String> {
+    let response = client(DOWNLOAD_TIMEOUT)?
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Download failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed: {} ({})", response.status(), url));
+    }
+    response
+        .bytes()
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("Download failed: {}", e))
+}
+
+/// Write `bytes` next to `target` and rename it over `target`, keeping the
+/// current file's permissions.
+fn swap_in(target: &Path, bytes: &[u8]) -> Result<(), String> {
+    let dir = target
+        .parent()
+        .ok_or("executable has no parent directory")?;
+    let name = target
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("pengy");
+    let staged = dir.join(format!(".{}.update-{}", name, std::process::id()));
+    let permission_hint = |e: io::Error| {
+        if e.kind() == io::ErrorKind::PermissionDenied {
+            format!(
+                "cannot write to {} ({}); re-run with permission to modify it",
+                dir.display(),
+                e
+            )
+        } else {
+            format!("failed to install update: {}", e)
+        }
+    };
+
+    fs::write(&staged, bytes).map_err(permission_hint)?;
+    if let Ok(metadata) = fs::metadata(target) {
+        let _ = fs::set_permissions(&staged, metadata.permissions());
+    }
+
+    // A running executable cannot be replaced on Windows, but it can be
+    // renamed out of the way.
+    #[cfg(windows)]
+    let backup = {
+        let backup = target.with_extension("old.exe");
+        let _ = fs::remove_file(&backup);
+        if let Err(e) = fs::rename(target, &backup) {
+            let _ = fs::remove_file(&staged);
+            return Err(permission_hint(e));
+        }
+        backup
+    };
+
+    if let Err(e) = fs::rename(&staged, target) {
+        let _ = fs::remove_file(&staged);
+        #[cfg(windows)]
+        let _ = fs::rename(&backup, target);
+        return Err(permission_hint(e));
+    }
+    Ok(())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn check_cache_path() -> PathBuf {
+    App::config_path().with_file_name(CHECK_CACHE_FILE)
+}
+
+/// Latest released version if it is newer than this build, using a cached
+/// answer when the last check was recent.
+fn newer_release_cached() -> Option<String> {
+    let cache_path = check_cache_path();
+    let cached = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<CheckCache>(&content).ok())
+        .filter(|cache| now_secs().saturating_sub(cache.checked) < CHECK_INTERVAL_SECS);
+    let latest = match cached {
+        Some(cache) => cache.latest,
+        None => {
+            let release = block_on(fetch_latest_release(CHECK_TIMEOUT)).ok()?.ok()?;
+            let cache = CheckCache {
+                checked: now_secs(),
+                latest: release.tag_name.trim_start_matches('v').to_string(),
+            };
+            if let Ok(json) = serde_json::to_string(&cache) {
+                let _ = fs::write(&cache_path, json);
+            }
+            cache.latest
+        }
+    };
+    is_newer(&latest, CURRENT_VERSION).then_some(latest)
+}
+
+/// Check for a newer release in the background; the receiver yields the
+/// new version, or `None` if this build is current or the check failed.
+pub(crate) fn spawn_update_check() -> Receiver<Option<String>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(newer_release_cached());
+    });
+    rx
+}
+
+pub(crate) fn print_self_update_usage() {
+    eprintln!("\nUsage: pengy self-update [--check] [--yes]");
+    eprintln!("\nDownloads the latest release for this platform from GitHub, verifies the");
+    eprintln!("signed SHA-256 checksum and replaces the running binary.");
+    eprintln!("\nOptions:");
+    eprintln!("  --check    Only report whether a newer version is available");
+    eprintln!("  --yes      Install without asking for confirmation");
+}
+
+/// Run `pengy self-update` with the arguments following the subcommand.
+pub(crate) fn run_self_update(args: &[String]) -> Result<(), String> {
+    let mut check_only = false;
+    let mut assume_yes = false;
+    for arg in args {
+        match arg.as_str() {
+            "--check" => check_only = true,
+            "--yes" | "-y" => assume_yes = true,
+            _ => return Err(format!("Unknown self-update argument: {}", arg)),
+        }
+    }
+
+    let release = block_on(fetch_latest_release(DOWNLOAD_TIMEOUT))??;
+    let latest = release.tag_name.trim_start_matches('v');
+    if !is_newer(latest, CURRENT_VERSION) {
+        println!("pengy {} is up to date.", CURRENT_VERSION);
+        return Ok(());
+    }
+    println!(
+        "pengy {} is available (installed: {}).",
+        latest, CURRENT_VERSION
+    );
+    if check_only {
+        return Ok(());
+    }
+
+    let asset_name = platform_asset_name();
+    let find_asset = |name: &str| release.assets.iter().find(|asset| asset.name == name);
+    let binary = find_asset(&asset_name)
+        .ok_or_else(|| format!("release {} has no {} binary", latest, asset_name))?;
+    let unverified = |asset: &str| {
+        format!(
+            "release {} has no {} file; refusing to install an unverified binary",
+            latest, asset
+        )
+    };
+    let checksums = find_asset(CHECKSUMS_ASSET).ok_or_else(|| unverified(CHECKSUMS_ASSET))?;
+    let signature = find_asset(SIGNATURE_ASSET).ok_or_else(|| unverified(SIGNATURE_ASSET))?;
+    if UPDATE_PUBLIC_KEY.is_none() {
+        return Err(NO_SIGNING_KEY.to_string());
+    }
+
+    let target = env::current_exe()
+        .and_then(fs::canonicalize)
+        .map_err(|e| format!("cannot locate the running executable: {}", e))?;
+    if !assume_yes {
+        print!("Replace {} with {}? [y/N] ", target.display(), latest);
+        let _ = io::stdout().flush();
+        let mut answer = String::new();
+        let _ = io::stdin().read_line(&mut answer);
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Update cancelled.");
+            return Ok(());
+        }
+    }
+
+    println!("Downloading {}...", asset_name);
+    let checksums = block_on(download(&checksums.browser_download_url))??;
+    let signature = block_on(download(&signature.browser_download_url))??;
+    verify_checksums(
+        &checksums,
+        &String::from_utf8_lossy(&signature),
+        UPDATE_PUBLIC_KEY,
+        latest,
+    )?;
+    let expected = expected_checksum(&String::from_utf8_lossy(&checksums), &asset_name)
+        .ok_or_else(|| format!("{} has no entry for {}", CHECKSUMS_ASSET, asset_name))?;
+    let bytes = block_on(download(&binary.browser_download_url))??;
+    let actual = sha256_hex(&bytes);
+    if actual != expected {
+        return Err(format!(
+            "checksum mismatch for {} (expected {}, got {}); nothing was installed",
+            asset_name, expected, actual
+        ));
+    }
+
+    swap_in(&target, &bytes)?;
+    let _ = fs::remove_file(check_cache_path());
+    println!("Updated pengy {} -> {}.", CURRENT_VERSION, latest);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_checksums_and_swap() {
+        assert!(is_newer("v0.2.0", "0.1.9"));
+        assert!(is_newer("1.10.0", "1.9.3"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("0.2.0-beta.1", "0.2.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+
+        let hash = sha256_hex(b"pengy");
+        let sums = format!(
+            "{}  pengy-linux-x86_64\n{} *pengy-windows-x86_64.exe\n",
+            "0".repeat(64),
+            hash.to_uppercase()
+        );
+        assert_eq!(
+            expected_checksum(&sums, "pengy-windows-x86_64.exe"),
+            Some(hash)
+        );
+        assert_eq!(expected_checksum(&sums, "pengy-linux"), None);
+
+        // Signed with a throwaway key, trusted comment "0.2.0"
+        let key = "RWQBAgMEBQYHCAOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+        let sums = b"0000  pengy-linux-x86_64\n";
+        let signature = "untrusted comment: pengy test key
+RUQBAgMEBQYHCGnLmfqQTVnBwCN1wll85YAqQQXU4bO7MAzTzLNj3zwaVJiARumrVZbxSosI7gs4Y+WIx5lCR+wvLnlH+Bnn1Qc=
+trusted comment: 0.2.0
+k0RBL04Bcaud9h7d8qJ6MTPCg0hMJsbL5S+USzbLVr/vySOizA1I6dneY6QVCmt6IjfYKisOlL5twzkNY+bQDg==";
+        assert!(verify_checksums(sums, signature, Some(key), "0.2.0").is_ok());
+        assert!(verify_checksums(b"tampered", signature, Some(key), "0.2.0").is_err());
+        assert!(verify_checksums(sums, "", Some(key), "0.2.0").is_err());
+        assert!(verify_checksums(sums, signature, None, "0.2.0").is_err());
+        // A validly signed older SHA256SUMS cannot be paired with a newer tag
+        let err = verify_checksums(sums, signature, Some(key), "0.3.0").unwrap_err();
+        assert!(err.contains("signed for version"), "{}", err);
+
+        // Test vector from the minisign-verify documentation, whose trusted
+        // comment is minisign's default rather than a version
+        let key = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+        let signature = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1633700835\tfile:test\tprehashed
+wLMDjy9FLAuxZ3q4NlEvkgtyhrr0gtTu6KC4KBJdITbbOeAi1zBIYo0v4iTgt8jJpIidRJnp94ABQkJAgAooBQ==";
+        assert!(verify_checksums(b"test", signature, Some(key), "0.2.0").is_err());
+
+        let dir = env::temp_dir().join(format!("pengy-swap-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("pengy");
+        fs::write(&target, b"old").unwrap();
+        swap_in(&target, b"new").unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"new");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,390 @@
+//! Push-to-talk voice input (the `voice` cargo feature). Ctrl+T starts
+//! recording from the default microphone and pressing it again stops and
+//! transcribes; the transcript is inserted into the input box.
+//!
+//! Recording uses whichever of sox (`rec`), `arecord` or `ffmpeg` is
+//! installed. Transcription runs locally through the whisper.cpp CLI when
+//! `PENGY_WHISPER_MODEL` points at a ggml model, and otherwise through an
+//! OpenAI-compatible `/audio/transcriptions` endpoint:
+//!
+//! - `PENGY_WHISPER_BIN`: whisper.cpp binary (default: first of `whisper-cli`,
+//!   `whisper-cpp`, `whisper` on PATH)
+//! - `PENGY_TRANSCRIBE_URL`: API base URL (default `https://api.openai.com/v1`)
+//! - `PENGY_TRANSCRIBE_API_KEY` / `OPENAI_API_KEY`: API key. The configured
+//!   model key is only reused when the transcription URL is the model's own
+//!   base URL, so it is never sent to a different provider
+//! - `PENGY_TRANSCRIBE_MODEL`: API model (default `whisper-1`)
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_TRANSCRIBE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_TRANSCRIBE_MODEL: &str = "whisper-1";
+/// Recordings shorter than this are treated as accidental key presses.
+const MIN_RECORDING: Duration = Duration::from_millis(400);
+
+struct Recording {
+    child: Child,
+    path: PathBuf,
+    started: Instant,
+}
+
+/// Voice input state held by the app: at most one recording or
+/// transcription is in flight.
+#[derive(Default)]
+pub(crate) struct VoiceInput {
+    recording: Option<Recording>,
+    transcription: Option<Receiver<Result<String, String>>>,
+}
+
+impl VoiceInput {
+    /// Short label for the status bar while recording or transcribing.
+    pub(crate) fn status(&self) -> Option<&'static str> {
+        if self.recording.is_some() {
+            Some("● Recording (Ctrl+T to stop)")
+        } else if self.transcription.is_some() {
+            Some("Transcribing...")
+        } else {
+            None
+        }
+    }
+
+    /// Start recording, or stop the current recording and transcribe it in
+    /// the background. `model_key` is the fallback key for API transcription,
+    /// used only when the transcription URL equals `model_base_url`.
+    pub(crate) fn toggle(&mut self, model_key: &str, model_base_url: &str) -> Result<(), String> {
+        if self.transcription.is_some() {
+            return Err("still transcribing the previous recording".to_string());
+        }
+        let Some(recording) = self.recording.take() else {
+            self.recording = Some(start_recording()?);
+            return Ok(());
+        };
+
+        let elapsed = recording.started.elapsed();
+        stop_recording(recording.child);
+        if elapsed < MIN_RECORDING {
+            let _ = std::fs::remove_file(&recording.path);
+            return Err("recording too short".to_string());
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let model_key = model_key.to_string();
+        let model_base_url = model_base_url.to_string();
+        std::thread::spawn(move || {
+            let result = transcribe(&recording.path, &model_key, &model_base_url);
+            let _ = std::fs::remove_file(&recording.path);
+            let _ = tx.send(result);
+        });
+        self.transcription = Some(rx);
+        Ok(())
+    }
+
+    /// The finished transcription, if one has completed since the last call.
+    pub(crate) fn poll(&mut self) -> Option<Result<String, String>> {
+        let result = match self.transcription.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Err("transcription stopped unexpectedly".to_string())
+            }
+        };
+        self.transcription = None;
+        Some(result)
+    }
+}
+
+impl Drop for VoiceInput {
+    fn drop(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            stop_recording(recording.child);
+            let _ = std::fs::remove_file(&recording.path);
+        }
+    }
+}
+
+fn start_recording() -> Result<Recording, String> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = env::temp_dir().join(format!("pengy-voice-{}-{}.wav", std::process::id(), stamp));
+    let file = path.to_string_lossy().to_string();
+
+    // 16 kHz mono 16-bit PCM is what whisper expects
+    let input = if cfg!(target_os = "macos") {
+        "avfoundation"
+    } else if cfg!(target_os = "windows") {
+        "dshow"
+    } else {
+        "pulse"
+    };
+    let device = if cfg!(target_os = "macos") {
+        ":0"
+    } else {
+        "default"
+    };
+    let recorders: Vec<(&str, Vec<&str>)> = vec![
+        (
+            "rec",
+            vec!["-q", "-c", "1", "-r", "16000", "-b", "16", &file],
+        ),
+        (
+            "arecord",
+            vec!["-q", "-f", "S16_LE", "-c", "1", "-r", "16000", &file],
+        ),
+        (
+            "ffmpeg",
+            vec![
+                "-loglevel",
+                "error",
+                "-f",
+                input,
+                "-i",
+                device,
+                "-ac",
+                "1",
+                "-ar",
+                "16000",
+                "-y",
+                &file,
+            ],
+        ),
+    ];
+
+    let mut tried = Vec::new();
+    for (program, args) in recorders {
+        match Command::new(program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => {
+                return Ok(Recording {
+                    child,
+                    path,
+                    started: Instant::now(),
+                });
+            }
+            Err(_) => tried.push(program),
+        }
+    }
+    Err(format!(
+        "no audio recorder found (install one of: {})",
+        tried.join(", ")
+    ))
+}
+
+/// Ask the recorder to finish so it writes a complete WAV header, killing it
+/// if it does not exit promptly.
+fn stop_recording(mut child: Child) {
+    #[cfg(unix)]
+    {
+        let _ = Command::new("kill")
+            .args(["-INT", &child.id().to_string()])
+            .status();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = child.try_wait() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn transcribe(audio: &Path, model_key: &str, model_base_url: &str) -> Result<String, String> {
+    let text = match env::var("PENGY_WHISPER_MODEL") {
+        Ok(model) if !model.trim().is_empty() => transcribe_local(audio, &model)?,
+        _ => transcribe_api(audio, model_key, model_base_url)?,
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return Err("no speech detected".to_string());
+    }
+    Ok(text)
+}
+
+fn transcribe_local(audio: &Path, model: &str) -> Result<String, String> {
+    let binaries = match env::var("PENGY_WHISPER_BIN") {
+        Ok(bin) if !bin.trim().is_empty() => vec![bin],
+        _ => ["whisper-cli", "whisper-cpp", "whisper"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+    };
+    for bin in &binaries {
+        let output = Command::new(bin)
+            .args(["-m", model, "-nt", "-np", "-f"])
+            .arg(audio)
+            .stdin(Stdio::null())
+            .output();
+        match output {
+            Ok(out) if out.status.success() => {
+                return Ok(String::from_utf8_lossy(&out.stdout).to_string());
+            }
+            Ok(out) => {
+                return Err(format!(
+                    "{} failed: {}",
+                    bin,
+                    String::from_utf8_lossy(&out.stderr).trim()
+                ));
+            }
+            Err(_) => continue,
+        }
+    }
+    Err(format!(
+        "whisper.cpp not found (tried {}); set PENGY_WHISPER_BIN",
+        binaries.join(", ")
+    ))
+}
+
+/// Pick the key for `base_url`: an explicit transcription key wins, and the
+/// model key is only used when `base_url` is the model's own endpoint.
+fn transcription_key(
+    base_url: &str,
+    explicit_key: Option<String>,
+    model_key: &str,
+    model_base_url: &str,
+) -> Result<String, String> {
+    if let Some(key) = explicit_key {
+        return Ok(key);
+    }
+    let same_endpoint = |url: &str| url.trim().trim_end_matches('/').to_ascii_lowercase();
+    if !model_key.is_empty() && same_endpoint(base_url) == same_endpoint(model_base_url) {
+        return Ok(model_key.to_string());
+    }
+    Err(format!(
+        "no API key for transcription at {}; set PENGY_TRANSCRIBE_API_KEY, OPENAI_API_KEY or PENGY_WHISPER_MODEL",
+        base_url
+    ))
+}
+
+fn transcribe_api(audio: &Path, model_key: &str, model_base_url: &str) -> Result<String, String> {
+    let base_url = env::var("PENGY_TRANSCRIBE_URL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TRANSCRIBE_URL.to_string());
+    let explicit_key = ["PENGY_TRANSCRIBE_API_KEY", "OPENAI_API_KEY"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|v| !v.trim().is_empty()));
+    let api_key = transcription_key(&base_url, explicit_key, model_key, model_base_url)?;
+    let model = env::var("PENGY_TRANSCRIBE_MODEL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TRANSCRIBE_MODEL.to_string());
+
+    let audio_bytes =
+        std::fs::read(audio).map_err(|e| format!("failed to read recording: {}", e))?;
+    let boundary = format!(
+        "pengy-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    );
+    let body = multipart_body(&boundary, &model, &audio_bytes);
+    let url = format!("{}/audio/transcriptions", base_url.trim_end_matches('/'));
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    rt.block_on(async {
+        let response = reqwest::Client::new()
+            .post(&url)
+            .bearer_auth(api_key)
+            .header(
+                "Content-Type",
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("transcription request failed: {}", e))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(format!(
+                "transcription failed ({}): {}",
+                status,
+                text.trim()
+            ));
+        }
+        let json: serde_json::Value =
+            serde_json::from_str(&text).map_err(|e| format!("unexpected response: {}", e))?;
+        json.get("text")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string())
+            .ok_or_else(|| "response has no transcript".to_string())
+    })
+}
+
+/// A `multipart/form-data` body with the `model` field and the WAV file.
+fn multipart_body(boundary: &str, model: &str, audio: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(audio.len() + 512);
+    body.extend_from_slice(
+        format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\n{model}\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"voice.wav\"\r\n\
+             Content-Type: audio/wav\r\n\r\n",
+            b = boundary,
+            model = model
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(audio);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multipart_body_wraps_model_and_audio() {
+        let body = multipart_body("XYZ", "whisper-1", b"RIFF");
+        let text = String::from_utf8(body).unwrap();
+        assert!(text.starts_with(
+            "--XYZ\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n--XYZ\r\n"
+        ));
+        assert!(text.contains(
+            "filename=\"voice.wav\"\r\nContent-Type: audio/wav\r\n\r\nRIFF\r\n--XYZ--\r\n"
+        ));
+    }
+
+    #[test]
+    fn model_key_is_only_reused_for_its_own_endpoint() {
+        let openrouter = "https://openrouter.ai/api/v1";
+        assert!(transcription_key(DEFAULT_TRANSCRIBE_URL, None, "sk-or", openrouter).is_err());
+        assert_eq!(
+            transcription_key(
+                DEFAULT_TRANSCRIBE_URL,
+                None,
+                "sk-oa",
+                "https://api.openai.com/v1/"
+            )
+            .unwrap(),
+            "sk-oa"
+        );
+        assert_eq!(
+            transcription_key(
+                DEFAULT_TRANSCRIBE_URL,
+                Some("sk-t".to_string()),
+                "sk-or",
+                openrouter
+            )
+            .unwrap(),
+            "sk-t"
+        );
+        assert!(transcription_key(openrouter, None, "", openrouter).is_err());
+    }
+}
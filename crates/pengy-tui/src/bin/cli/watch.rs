@@ -0,0 +1,395 @@
+//! `pengy watch`: re-run a check command whenever the workspace changes and,
+//! when it fails, hand the failure to an agent run. A cooldown between runs
+//! and a cap on consecutive runs keep a stubborn failure from looping forever.
+//!
+//! Nobody is expected to answer prompts while watching, so tool approvals and
+//! selections are denied unless watch is started with `--yes`.
+
+use crate::app::App;
+use crate::command::{parse_agent_type, run_cmd_mode};
+use pengy_agent::tool::approval::approval;
+use pengy_agent::util::workspace_files::workspace_files;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::runtime::Runtime;
+
+const DEFAULT_PROMPT: &str = "Fix the failure reported by the check command below.";
+const DEFAULT_COOLDOWN_SECS: u64 = 30;
+const DEFAULT_MAX_ITERATIONS: usize = 5;
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+const MAX_WATCHED_FILES: usize = 50_000;
+/// Failure output handed to the agent is cut to its tail; the end of a test
+/// or build log is where the errors and summary are.
+const MAX_FAILURE_CHARS: usize = 12_000;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct WatchOptions {
+    pub on_fail: String,
+    pub prompt: String,
+    pub cooldown: Duration,
+    pub max_iterations: usize,
+    pub agent: String,
+    pub model: Option<String>,
+    pub provider: Option<String>,
+    pub api_key: Option<String>,
+    pub base_url: Option<String>,
+    /// Approve every tool request instead of denying it.
+    pub assume_yes: bool,
+}
+
+/// Parse the arguments following `pengy watch`.
+pub(crate) fn parse_watch_args(args: &[String]) -> Result<WatchOptions, String> {
+    let mut options = WatchOptions {
+        on_fail: String::new(),
+        prompt: DEFAULT_PROMPT.to_string(),
+        cooldown: Duration::from_secs(DEFAULT_COOLDOWN_SECS),
+        max_iterations: DEFAULT_MAX_ITERATIONS,
+        agent: "coder".to_string(),
+        model: None,
+        provider: None,
+        api_key: None,
+        base_url: None,
+        assume_yes: false,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        if flag == "--yes" {
+            options.assume_yes = true;
+            i += 1;
+            continue;
+        }
+        let value = args
+            .get(i + 1)
+            .cloned()
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag {
+            "--on-fail" => options.on_fail = value,
+            "--prompt" => options.prompt = value,
+            "--cooldown" => {
+                let secs = value
+                    .parse()
+                    .map_err(|_| format!("--cooldown expects seconds, got '{}'", value))?;
+                options.cooldown = Duration::from_secs(secs);
+            }
+            "--max-iterations" => {
+                options.max_iterations =
+                    value.parse().ok().filter(|n| *n > 0).ok_or_else(|| {
+                        format!(
+                            "--max-iterations expects a positive number, got '{}'",
+                            value
+                        )
+                    })?;
+            }
+            "--agent" => options.agent = value,
+            "--model" => options.model = Some(value),
+            "--provider" => options.provider = Some(value),
+            "--api-key" => options.api_key = Some(value),
+            "--base-url" => options.base_url = Some(value),
+            _ => return Err(format!("Unknown watch argument: {}", flag)),
+        }
+        i += 2;
+    }
+
+    if options.on_fail.trim().is_empty() {
+        return Err("--on-fail \"<check command>\" is required".to_string());
+    }
+    Ok(options)
+}
+
+pub(crate) fn print_watch_usage() {
+    eprintln!(
+        "\nUsage: pengy watch --on-fail \"<check command>\" [--prompt \"<prompt>\"] [options]"
+    );
+    eprintln!("\nRuns the check command whenever a file in the workspace changes. When it fails,");
+    eprintln!("an agent run is started with the prompt and the command's output.");
+    eprintln!("\nOptions:");
+    eprintln!("  --on-fail \"<command>\"      Check command, e.g. \"cargo test\" (required)");
+    eprintln!(
+        "  --prompt \"<prompt>\"        Instructions for the agent (default: fix the failure)"
+    );
+    eprintln!(
+        "  --cooldown <seconds>        Minimum time between agent runs (default: {})",
+        DEFAULT_COOLDOWN_SECS
+    );
+    eprintln!(
+        "  --max-iterations <n>        Agent runs allowed before the check passes again (default: {})",
+        DEFAULT_MAX_ITERATIONS
+    );
+    eprintln!("  --agent <agent-type>        Agent to run (default: coder)");
+    eprintln!("  --yes                       Approve tool requests (denied by default)");
+    eprintln!("  --model, --provider, --api-key, --base-url");
+    eprintln!("                              Override the model saved by the TUI");
+    eprintln!("\nExample:");
+    eprintln!("  pengy watch --on-fail \"cargo test\" --prompt \"fix the failing tests\"");
+}
+
+struct CheckResult {
+    success: bool,
+    output: String,
+}
+
+fn run_check(command: &str) -> Result<CheckResult, Box<dyn Error>> {
+    let output = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| format!("failed to run '{}': {}", command, e))?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&stderr);
+    }
+    Ok(CheckResult {
+        success: output.status.success(),
+        output: text,
+    })
+}
+
+/// The agent prompt for a failed check: the user's instructions, then the
+/// command and the tail of its output.
+fn failure_prompt(prompt: &str, command: &str, output: &str) -> String {
+    let total = output.chars().count();
+    let tail: String = if total > MAX_FAILURE_CHARS {
+        let skipped = total - MAX_FAILURE_CHARS;
+        format!(
+            "... [{} earlier characters omitted]\n{}",
+            skipped,
+            output.chars().skip(skipped).collect::<String>()
+        )
+    } else {
+        output.to_string()
+    };
+    format!(
+        "{}\n\nThe check command `{}` failed with this output:\n```\n{}\n```\n\nWhen you are done, the command will be re-run to verify the fix.",
+        prompt,
+        command,
+        tail.trim_end()
+    )
+}
+
+type Snapshot = HashMap<PathBuf, (Option<SystemTime>, u64)>;
+
+fn snapshot(root: &Path) -> Snapshot {
+    workspace_files::collect_files(root, MAX_WATCHED_FILES, u64::MAX)
+        .into_iter()
+        .filter_map(|path| {
+            let meta = std::fs::metadata(&path).ok()?;
+            Some((path, (meta.modified().ok(), meta.len())))
+        })
+        .collect()
+}
+
+/// Block until the workspace differs from `before` and has then been quiet
+/// for one poll interval, so a burst of saves triggers a single check.
+fn wait_for_change(root: &Path, before: &Snapshot) {
+    let mut current = loop {
+        sleep(POLL_INTERVAL);
+        let now = snapshot(root);
+        if &now != before {
+            break now;
+        }
+    };
+    loop {
+        sleep(POLL_INTERVAL);
+        let now = snapshot(root);
+        if now == current {
+            break;
+        }
+        current = now;
+    }
+
+    let changed: Vec<String> = current
+        .iter()
+        .filter(|(path, stamp)| before.get(*path) != Some(*stamp))
+        .map(|(path, _)| {
+            path.strip_prefix(root)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        })
+        .chain(
+            before
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .map(|path| {
+                    format!(
+                        "{} (deleted)",
+                        path.strip_prefix(root).unwrap_or(path).display()
+                    )
+                }),
+        )
+        .collect();
+    let shown: Vec<&str> = changed.iter().take(5).map(String::as_str).collect();
+    let more = changed.len().saturating_sub(shown.len());
+    println!(
+        "[watch] changed: {}{}",
+        shown.join(", "),
+        if more > 0 {
+            format!(" and {} more", more)
+        } else {
+            String::new()
+        }
+    );
+}
+
+pub(crate) fn run_watch_mode(options: WatchOptions, rt: &Runtime) -> Result<(), Box<dyn Error>> {
+    let agent_type = parse_agent_type(&options.agent)?;
+    let config = App::load_config();
+    let saved_model = config.selected_model;
+    let model = options
+        .model
+        .clone()
+        .or_else(|| saved_model.as_ref().map(|m| m.name.clone()))
+        .ok_or("No model configured: pass --model or select one in the TUI first")?;
+    let provider = options
+        .provider
+        .clone()
+        .or_else(|| saved_model.as_ref().map(|m| m.provider.clone()))
+        .unwrap_or_else(|| "Custom".to_string());
+    let api_key = options
+        .api_key
+        .clone()
+        .filter(|k| !k.is_empty())
+        .or_else(|| Some(config.api_key).filter(|k| !k.is_empty()))
+        .ok_or("No API key configured: pass --api-key or set one in the TUI first")?;
+    let base_url = options.base_url.clone().or_else(|| {
+        saved_model
+            .as_ref()
+            .filter(|m| options.model.is_none() || options.model.as_deref() == Some(&m.name))
+            .map(|m| m.base_url.clone())
+    });
+
+    let assume_yes = options.assume_yes;
+    approval::set_handler(Box::new(move |request| {
+        println!(
+            "[watch] {} {} request: {}",
+            if assume_yes { "approved" } else { "denied" },
+            request.tool,
+            request.action
+        );
+        assume_yes
+    }));
+    approval::set_selection_handler(Box::new(move |request| {
+        println!(
+            "[watch] {} {}: {}",
+            if assume_yes { "approved" } else { "denied" },
+            request.tool,
+            request.title
+        );
+        assume_yes.then(|| vec![true; request.items.len()])
+    }));
+
+    let root = std::env::current_dir()?;
+    println!(
+        "[watch] watching {} (check: {}, cooldown: {}s, max iterations: {}, approvals: {})",
+        root.display(),
+        options.on_fail,
+        options.cooldown.as_secs(),
+        options.max_iterations,
+        if assume_yes { "approve" } else { "deny" }
+    );
+
+    let mut agent_runs = 0;
+    let mut last_run: Option<Instant> = None;
+    loop {
+        println!("[watch] running: {}", options.on_fail);
+        let check = run_check(&options.on_fail)?;
+        if check.success {
+            if agent_runs > 0 {
+                println!("[watch] check passes after {} agent run(s)", agent_runs);
+            } else {
+                println!("[watch] check passed");
+            }
+            agent_runs = 0;
+        } else if agent_runs >= options.max_iterations {
+            println!(
+                "[watch] check still failing after {} agent run(s); pausing agent runs until it passes again",
+                agent_runs
+            );
+        } else {
+            if let Some(last) = last_run {
+                let elapsed = last.elapsed();
+                if elapsed < options.cooldown {
+                    let wait = options.cooldown - elapsed;
+                    println!("[watch] check failed; cooling down for {}s", wait.as_secs());
+                    sleep(wait);
+                }
+            }
+            agent_runs += 1;
+            println!(
+                "[watch] check failed; starting agent run {}/{}",
+                agent_runs, options.max_iterations
+            );
+            let prompt = failure_prompt(&options.prompt, &options.on_fail, &check.output);
+            // A failed run still counts towards --max-iterations
+            if let Err(err) = rt.block_on(run_cmd_mode(
+                prompt,
+                agent_type,
+                model.clone(),
+                provider.clone(),
+                api_key.clone(),
+                base_url.clone(),
+            )) {
+                eprintln!("[watch] agent run {} failed: {}", agent_runs, err);
+            }
+            last_run = Some(Instant::now());
+            // Verify the agent's changes straight away instead of waiting
+            // for another edit.
+            continue;
+        }
+
+        // Snapshot after the check so files it writes don't retrigger it
+        let before = snapshot(&root);
+        println!("[watch] waiting for changes...");
+        wait_for_change(&root, &before);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn watch_args_require_check_command_and_apply_defaults() {
+        assert!(parse_watch_args(&args(&["--prompt", "fix it"])).is_err());
+        assert!(parse_watch_args(&args(&["--on-fail"])).is_err());
+        assert!(parse_watch_args(&args(&["--on-fail", "make", "--max-iterations", "0"])).is_err());
+
+        let options =
+            parse_watch_args(&args(&["--on-fail", "cargo test", "--cooldown", "5"])).unwrap();
+        assert_eq!(options.on_fail, "cargo test");
+        assert_eq!(options.prompt, DEFAULT_PROMPT);
+        assert_eq!(options.cooldown, Duration::from_secs(5));
+        assert_eq!(options.max_iterations, DEFAULT_MAX_ITERATIONS);
+        assert!(!options.assume_yes);
+
+        let options = parse_watch_args(&args(&["--yes", "--on-fail", "make"])).unwrap();
+        assert!(options.assume_yes);
+        assert_eq!(options.on_fail, "make");
+    }
+
+    #[test]
+    fn failure_prompt_keeps_the_tail_of_long_output() {
+        let output = format!("{}END", "x".repeat(MAX_FAILURE_CHARS * 2));
+        let prompt = failure_prompt("fix", "make", &output);
+        assert!(prompt.starts_with("fix\n\nThe check command `make` failed"));
+        assert!(prompt.contains("earlier characters omitted"));
+        assert!(prompt.contains("END\n```"));
+        assert!(prompt.len() < MAX_FAILURE_CHARS + 500);
+    }
+}
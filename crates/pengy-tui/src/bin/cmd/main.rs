@@ -7,6 +7,7 @@ use pengy_agent::agent::issue_agent::issue_agent::create_issue_agent;
 use pengy_agent::agent::pengy_agent::pengy_agent::run_pengy_agent;
 use pengy_agent::agent::test_agent::test_agent::create_test_agent;
 use pengy_agent::model::model::model::Model;
+use pengy_agent::tool::approval::approval;
 use std::env;
 use std::error::Error;
 
@@ -144,6 +145,18 @@ fn print_usage() {
     eprintln!("  pengy-cmd --apikey=sk-... --model=custom/model --prompt=\"Do something\" --base-url=https://api.example.com/v1");
 }
 
+/// Route approval and selection requests: accept everything under `--yolo`,
+/// otherwise ask on stdin.
+fn install_approval_handlers(yolo: bool) {
+    if yolo {
+        approval::set_handler(Box::new(|_| true));
+        approval::set_selection_handler(Box::new(|request| Some(vec![true; request.items.len()])));
+    } else {
+        approval::set_handler(approval::stdin_handler());
+        approval::set_selection_handler(approval::stdin_selection_handler());
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     let (api_key, agent_type, model_name, prompt, custom_base_url, yolo) = parse_args()?;
@@ -159,6 +172,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     if yolo {
         eprintln!("[YOLO MODE] Auto-approving all actions");
     }
+    install_approval_handlers(yolo);
 
     eprintln!("Running agent in non-interactive mode...");
     eprintln!("Agent: {:?}", agent_type);
@@ -260,3 +274,27 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yolo_accepts_every_selection() {
+        install_approval_handlers(true);
+        let items = vec![
+            approval::SelectionItem {
+                group: "a.rs".to_string(),
+                label: "line 1".to_string(),
+                detail: String::new(),
+            };
+            3
+        ];
+        assert!(approval::request("find_replace", "write files"));
+        assert_eq!(
+            approval::select("find_replace", "Replace", items),
+            Some(vec![true; 3])
+        );
+        approval::clear_handler();
+        approval::clear_selection_handler();
+    }
+}
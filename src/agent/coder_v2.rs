@@ -2,6 +2,7 @@ pub mod coder_v2 {
     use crate::agent::agent::agent::Agent;
     use crate::model::model::model::Model;
     use crate::prompt::coder::coder_v2_system_prompt;
+    use crate::tool::archive::archive::ArchiveTool;
     use crate::tool::bash::bash::BashTool;
     use crate::tool::docs_researcher::docs_researcher::DocsResearcherTool;
     use crate::tool::edit::edit::EditTool;
@@ -9,7 +10,9 @@ pub mod coder_v2 {
     use crate::tool::file_manager::file_manager::FileManagerTool;
     use crate::tool::find_replace::find_replace::FindReplaceTool;
     use crate::tool::grep::grep::GrepTool;
+    use crate::tool::move_file::move_file::MoveFileTool;
     use crate::tool::read_file::read_file::ReadFileTool;
+    use crate::tool::restore_file::restore_file::RestoreFileTool;
     use crate::tool::summarizer::summarizer::SummarizerTool;
     use crate::tool::think::think::ThinkTool;
     use crate::tool::todo::todo::TodoTool;
@@ -18,7 +21,7 @@ pub mod coder_v2 {
 
     /// Create a coder agent using the tools listed in the coder prompt.
     /// Tool order mirrors the prompt guidance:
-    /// grep -> read_file -> find_replace -> edit -> file_manager -> docs_researcher -> todo
+    /// grep -> read_file -> find_replace -> edit -> file_manager -> move_file -> restore_file -> archive -> docs_researcher -> todo
     /// -> web -> bash -> summarizer -> think -> end.
     pub fn create_coder_v2_agent(
         model: Model,
@@ -32,6 +35,9 @@ pub mod coder_v2 {
         let find_replace_tool = FindReplaceTool::new();
         let edit_tool = EditTool::new();
         let file_manager_tool = FileManagerTool::new();
+        let move_file_tool = MoveFileTool::new();
+        let restore_file_tool = RestoreFileTool::new();
+        let archive_tool = ArchiveTool::new();
         let docs_researcher_tool = DocsResearcherTool::new();
         let todo_tool = TodoTool::new();
         let web_tool = WebTool::new();
@@ -46,6 +52,9 @@ pub mod coder_v2 {
             Box::new(find_replace_tool),
             Box::new(edit_tool),
             Box::new(file_manager_tool),
+            Box::new(move_file_tool),
+            Box::new(restore_file_tool),
+            Box::new(archive_tool),
             Box::new(docs_researcher_tool),
             Box::new(todo_tool),
             Box::new(web_tool),
@@ -1,4 +1,4 @@
-pub mod agent;
+pub use pengy_core::agent::agent;
 pub mod code_researcher;
 pub mod coder;
 pub mod coder_v2;
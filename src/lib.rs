@@ -1,5 +1,11 @@
+//! Ready-made agents (coder, researcher, tester, ...) built from the
+//! `pengy-core` runtime and the `pengy-tools` tool set. Both are re-exported
+//! so `pengy_agent::model`, `pengy_agent::tool` and `pengy_agent::util` keep
+//! working.
+
+pub use pengy_core::model;
+pub use pengy_tools::util;
+
 pub mod agent;
-pub mod model;
 pub mod prompt;
 pub mod tool;
-pub mod util;
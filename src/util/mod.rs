@@ -1,3 +0,0 @@
-pub mod github_control;
-
-